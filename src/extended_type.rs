@@ -0,0 +1,105 @@
+/// This file defines the semantic types of the EXTENDED_TYPE extension.
+/// An extended type tells a client what a value means beyond its OSC TYPE,
+/// e.g. that three floats are a cartesian position or a string is a file path.
+///
+/// see [OSCQuery Proposal](https://github.com/Vidvox/OSCQueryProposal)
+///
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The OSCExtendedType enum defines the well known extended types,
+/// any other type can be used with `OSCExtendedType::Custom`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OSCExtendedType {
+    FilePath,
+    PositionCartesianXY,
+    PositionCartesianXYZ,
+    PositionSphericalAED,
+    PositionPolarAD,
+    PositionCylindricalRHZ,
+    OrientationQuaternion,
+    OrientationEuler,
+    OrientationAxis,
+    ColorRgbaFloat,
+    ColorRgbFloat,
+    ColorArgb8,
+    ColorHsv,
+    Custom(String),
+}
+
+impl std::fmt::Display for OSCExtendedType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OSCExtendedType::FilePath => write!(f, "filepath"),
+            OSCExtendedType::PositionCartesianXY => write!(f, "position.cartesian.xy"),
+            OSCExtendedType::PositionCartesianXYZ => write!(f, "position.cartesian.xyz"),
+            OSCExtendedType::PositionSphericalAED => write!(f, "position.spherical.aed"),
+            OSCExtendedType::PositionPolarAD => write!(f, "position.polar.ad"),
+            OSCExtendedType::PositionCylindricalRHZ => write!(f, "position.cylindrical.rhz"),
+            OSCExtendedType::OrientationQuaternion => write!(f, "orientation.quaternion"),
+            OSCExtendedType::OrientationEuler => write!(f, "orientation.euler"),
+            OSCExtendedType::OrientationAxis => write!(f, "orientation.axis"),
+            OSCExtendedType::ColorRgbaFloat => write!(f, "color.rgba.float"),
+            OSCExtendedType::ColorRgbFloat => write!(f, "color.rgb.float"),
+            OSCExtendedType::ColorArgb8 => write!(f, "color.argb8"),
+            OSCExtendedType::ColorHsv => write!(f, "color.hsv"),
+            OSCExtendedType::Custom(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<&str> for OSCExtendedType {
+    fn from(s: &str) -> Self {
+        match s {
+            "filepath" => OSCExtendedType::FilePath,
+            "position.cartesian.xy" => OSCExtendedType::PositionCartesianXY,
+            "position.cartesian.xyz" => OSCExtendedType::PositionCartesianXYZ,
+            "position.spherical.aed" => OSCExtendedType::PositionSphericalAED,
+            "position.polar.ad" => OSCExtendedType::PositionPolarAD,
+            "position.cylindrical.rhz" => OSCExtendedType::PositionCylindricalRHZ,
+            "orientation.quaternion" => OSCExtendedType::OrientationQuaternion,
+            "orientation.euler" => OSCExtendedType::OrientationEuler,
+            "orientation.axis" => OSCExtendedType::OrientationAxis,
+            "color.rgba.float" => OSCExtendedType::ColorRgbaFloat,
+            "color.rgb.float" => OSCExtendedType::ColorRgbFloat,
+            "color.argb8" => OSCExtendedType::ColorArgb8,
+            "color.hsv" => OSCExtendedType::ColorHsv,
+            _ => OSCExtendedType::Custom(s.to_string()),
+        }
+    }
+}
+
+impl Serialize for OSCExtendedType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("{}", self))
+    }
+}
+
+impl<'de> Deserialize<'de> for OSCExtendedType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(OSCExtendedType::from(s.as_str()))
+    }
+}
+
+#[test]
+fn extended_type_names() {
+    let types = [
+        OSCExtendedType::FilePath,
+        OSCExtendedType::PositionCartesianXYZ,
+        OSCExtendedType::ColorRgbaFloat,
+        OSCExtendedType::Custom("vendor.fader".to_string()),
+    ];
+    let json = serde_json::to_string(&types).unwrap();
+    assert_eq!(
+        json,
+        r#"["filepath","position.cartesian.xyz","color.rgba.float","vendor.fader"]"#
+    );
+    let deserialized: Vec<OSCExtendedType> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, types);
+}
@@ -0,0 +1,977 @@
+use crate::oscquery_types::{is_osc_pattern, ChangeCallback};
+use crate::receiver::BundleScheduler;
+use crate::{CoercionPolicy, OSCNode, OscQueryParameter, OscTransport, WriteRejection};
+use rosc::{OscError, OscMessage, OscType};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use tokio::sync::{broadcast, watch};
+
+/// number of events buffered for each subscriber before it starts lagging behind
+const EVENT_CAPACITY: usize = 256;
+
+/// Where a change of a VALUE came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeSource {
+    /// the application itself, e.g. `OscTreeHandle::set_value`
+    Local,
+    /// an OSC message received from the given address
+    Osc(SocketAddr),
+    /// a client connected to the OSCQuery WebSocket from the given address
+    WebSocket(SocketAddr),
+}
+
+/// The VALUE of a parameter and where it came from, as sent by `OscTreeHandle::watch`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub value: Vec<OscType>,
+    pub source: ChangeSource,
+}
+
+/// A change of the tree, as published by `OscTreeHandle::subscribe`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeEvent {
+    /// the VALUE of the parameter at `path` was set to `value` by `source`
+    ValueChanged {
+        path: String,
+        value: Vec<OscType>,
+        source: ChangeSource,
+    },
+    /// a new node was added at `path` (sub nodes of it are not reported separately)
+    PathAdded(String),
+    /// the node at `path` was removed including all of its sub nodes
+    PathRemoved(String),
+    /// the node at `old` was moved to `new` including all of its sub nodes
+    PathRenamed { old: String, new: String },
+    /// the attributes of the existing node at `path` changed
+    PathChanged(String),
+}
+
+/// A cloneable, thread safe handle to a live `OSCNode` tree.
+///
+/// The OSCQuery service keeps its own clone of the handle and answers every
+/// request from the current state of the tree. The application can therefore
+/// keep adding and updating parameters while the service is running,
+/// without restarting the listener.
+///
+/// ```
+/// use oscq_rs::{OSCNode, OscQueryParameter, OscTreeHandle};
+/// let tree = OscTreeHandle::new(OSCNode::root(None));
+/// let served = tree.clone(); // e.g. passed to `run_oscquery_service`
+/// tree.add(OscQueryParameter::new("/test/param".to_string(), rosc::OscType::Int(42)))
+///     .unwrap();
+/// assert!(served.read().get("/test/param".to_string()).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OscTreeHandle {
+    root: Arc<RwLock<OSCNode>>,
+    events: broadcast::Sender<TreeEvent>,
+    callbacks: Arc<RwLock<Callbacks<MessageFn>>>,
+    rejected: Arc<RwLock<Callbacks<RejectedFn>>>,
+    watchers: Arc<Mutex<HashMap<String, watch::Sender<ValueChange>>>>,
+    bundles: Arc<BundleScheduler>,
+}
+
+/// callback for OSC messages that were received and applied to the tree
+type MessageFn = dyn Fn(&OscMessage) + Send + Sync;
+
+/// callback for writes of clients that were rejected
+type RejectedFn = dyn Fn(&WriteRejection, &ChangeSource) + Send + Sync;
+
+/// the registered callbacks, closures can not be printed with `Debug`.
+/// They are called from a snapshot taken under the lock, so a callback
+/// can register further callbacks without deadlocking.
+struct Callbacks<F: ?Sized>(Arc<[Arc<F>]>);
+
+impl<F: ?Sized> Callbacks<F> {
+    fn push(&mut self, callback: Arc<F>) {
+        self.0 = self.0.iter().cloned().chain([callback]).collect();
+    }
+}
+
+impl<F: ?Sized> Default for Callbacks<F> {
+    fn default() -> Self {
+        Self(Arc::from(Vec::new()))
+    }
+}
+
+impl<F: ?Sized> std::fmt::Debug for Callbacks<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Callbacks({})", self.0.len())
+    }
+}
+
+impl OscTreeHandle {
+    /// create a new handle owning the given root node
+    pub fn new(root: OSCNode) -> Self {
+        Self {
+            root: Arc::new(RwLock::new(root)),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            callbacks: Arc::default(),
+            rejected: Arc::default(),
+            watchers: Arc::default(),
+            bundles: Arc::default(),
+        }
+    }
+
+    /// add a new Parameter to the shared tree.
+    /// Subscribers are notified with a `TreeEvent::PathAdded` for the topmost new node,
+    /// or a `TreeEvent::PathChanged` if the node already existed.
+    /// Replacing an existing parameter resets its VALUE, which is reported like `set_value`.
+    pub fn add(&self, parameter: OscQueryParameter) -> Result<(), OscError> {
+        let path = parameter.address().to_string();
+        let mut root = self.root.write().unwrap();
+        let added = root.first_missing(&path);
+        let replaced = root.get(path.clone()).is_ok_and(|node| node.is_parameter());
+        root.add(parameter)?;
+        let change = if replaced {
+            Some(root.value_and_callback(&path)?)
+        } else {
+            None
+        };
+        drop(root);
+
+        self.publish(match added {
+            Some(added) => TreeEvent::PathAdded(added),
+            None => TreeEvent::PathChanged(path.clone()),
+        });
+        if let Some((value, on_change)) = change {
+            self.notify(path, value, on_change, ChangeSource::Local);
+        }
+        Ok(())
+    }
+
+    /// replace an existing Parameter in the shared tree.
+    /// Subscribers are notified with a `TreeEvent::PathChanged`,
+    /// the new VALUE is reported like `set_value`.
+    pub fn replace(&self, parameter: OscQueryParameter) -> Result<(), OscError> {
+        let path = parameter.address().to_string();
+        let mut root = self.root.write().unwrap();
+        root.replace(parameter)?;
+        let (value, on_change) = root.value_and_callback(&path)?;
+        drop(root);
+
+        self.publish(TreeEvent::PathChanged(path.clone()));
+        self.notify(path, value, on_change, ChangeSource::Local);
+        Ok(())
+    }
+
+    /// remove the node at `path` and all of its sub nodes from the shared tree.
+    /// Subscribers are notified with a `TreeEvent::PathRemoved` for the topmost removed node,
+    /// which can be a container node that was pruned.
+    pub fn remove(&self, path: String) -> Result<OSCNode, OscError> {
+        let mut root = self.root.write().unwrap();
+        let node = root.remove(path.clone())?;
+        let removed = root.first_missing(&path).unwrap_or(path);
+        let prefix = normalize_path(&removed);
+        self.watchers
+            .lock()
+            .unwrap()
+            .retain(|key, _| !is_sub_path(key, &prefix));
+        drop(root);
+
+        self.publish(TreeEvent::PathRemoved(removed));
+        Ok(node)
+    }
+
+    /// move the node at `from` and all of its sub nodes to `to`.
+    /// Subscribers are notified with a `TreeEvent::PathRenamed`.
+    pub fn rename(&self, from: String, to: String) -> Result<(), OscError> {
+        let mut root = self.root.write().unwrap();
+        root.rename(from.clone(), to.clone())?;
+        let (old, new) = (normalize_path(&from), normalize_path(&to));
+        let mut watchers = self.watchers.lock().unwrap();
+        let moved: Vec<String> = watchers
+            .keys()
+            .filter(|key| is_sub_path(key, &old))
+            .cloned()
+            .collect();
+        for key in moved {
+            let watcher = watchers.remove(&key).unwrap();
+            watchers.insert(format!("{}{}", new, &key[old.len()..]), watcher);
+        }
+        drop(watchers);
+        drop(root);
+
+        self.publish(TreeEvent::PathRenamed { old: from, new: to });
+        Ok(())
+    }
+
+    /// remove all nodes from the shared tree, the root and its HOST_INFO are kept.
+    /// Subscribers are notified with a `TreeEvent::PathRemoved` for every removed top level node.
+    pub fn clear(&self) {
+        let mut root = self.root.write().unwrap();
+        let removed: Vec<String> = root
+            .contents
+            .iter()
+            .flat_map(|contents| contents.values())
+            .map(|node| node.full_path.clone())
+            .collect();
+        root.clear();
+        self.watchers.lock().unwrap().clear();
+        drop(root);
+
+        for path in removed {
+            self.publish(TreeEvent::PathRemoved(path));
+        }
+    }
+
+    /// get a copy of the current VALUE of the parameter at `path`
+    pub fn value(&self, path: String) -> Result<Vec<OscType>, OscError> {
+        self.read().value(path).map(|values| values.to_vec())
+    }
+
+    /// set the `CoercionPolicy` of the parameter at `path`, see `OSCNode::set_coercion`
+    pub fn set_coercion(&self, path: String, coercion: CoercionPolicy) -> Result<(), OscError> {
+        self.root.write().unwrap().set_coercion(path, coercion)
+    }
+
+    /// set the VALUE of the parameter at `path`, checked against its TYPE and clipped to its RANGE.
+    /// Subscribers are notified with a `TreeEvent::ValueChanged` carrying the stored (clipped) values.
+    pub fn set_value(&self, path: String, values: Vec<OscType>) -> Result<(), OscError> {
+        self.set_value_from(path, values, ChangeSource::Local)
+    }
+
+    /// set the VALUE of the parameter at `path` like `set_value`, on behalf of `source`.
+    /// Like `set_value` it does not check the ACCESS of the parameter,
+    /// writes of clients are applied with `apply_message` instead.
+    /// The `on_change` callback of the parameter, watchers and subscribers are notified
+    /// once the tree is unlocked again.
+    pub fn set_value_from(
+        &self,
+        path: String,
+        values: Vec<OscType>,
+        source: ChangeSource,
+    ) -> Result<(), OscError> {
+        let (value, on_change) = self.root.write().unwrap().store_value(&path, values)?;
+        self.notify(path, value, on_change, source);
+        Ok(())
+    }
+
+    /// tell the `on_change` callback, watchers and subscribers about a new VALUE,
+    /// must be called without holding the lock of the tree.
+    /// Subscribers get the normalized `path`, e.g. `/gain` for `/gain/`.
+    fn notify(
+        &self,
+        path: String,
+        value: Vec<OscType>,
+        on_change: Option<ChangeCallback>,
+        source: ChangeSource,
+    ) {
+        if let Some(on_change) = on_change {
+            on_change.call(&value, &source);
+        }
+        let path = normalize_path(&path);
+        let mut watchers = self.watchers.lock().unwrap();
+        if let Some(watcher) = watchers.get(&path) {
+            let change = ValueChange {
+                value: value.clone(),
+                source,
+            };
+            if watcher.send(change).is_err() {
+                // all receivers are gone
+                watchers.remove(&path);
+            }
+        }
+        drop(watchers);
+        self.publish(TreeEvent::ValueChanged {
+            path,
+            value,
+            source,
+        });
+    }
+
+    /// watch the VALUE of the parameter at `path`.
+    /// The receiver starts with the current value (reported as `ChangeSource::Local`)
+    /// and is updated with every following change and its source.
+    /// The receiver follows the parameter when it is renamed
+    /// and is closed when the parameter is removed.
+    /// ```
+    /// use oscq_rs::{OSCNode, OscQueryParameter, OscTreeHandle};
+    /// use rosc::OscType;
+    /// let tree = OscTreeHandle::new(OSCNode::root(None));
+    /// tree.add(OscQueryParameter::new("/gain".to_string(), OscType::Float(0.0))).unwrap();
+    /// let gain = tree.watch("/gain".to_string()).unwrap();
+    /// tree.set_value("/gain".to_string(), vec![OscType::Float(0.5)]).unwrap();
+    /// assert_eq!(gain.borrow().value, vec![OscType::Float(0.5)]);
+    /// ```
+    pub fn watch(&self, path: String) -> Result<watch::Receiver<ValueChange>, OscError> {
+        // a change stored after the value was read is sent to the new watcher,
+        // since it is only published once the tree is unlocked
+        let root = self.read();
+        let value = root.value(path.clone())?.to_vec();
+        let mut watchers = self.watchers.lock().unwrap();
+        let watcher = watchers.entry(normalize_path(&path)).or_insert_with(|| {
+            watch::channel(ValueChange {
+                value,
+                source: ChangeSource::Local,
+            })
+            .0
+        });
+        Ok(watcher.subscribe())
+    }
+
+    /// apply an OSC message received from the network:
+    /// the parameter at the address has to be writable (ACCESS) and the arguments have to match its TYPE.
+    /// An address pattern (e.g. `/mixer/ch*/gain`) sets every writable parameter it matches,
+    /// it fails only if none of them could be set.
+    /// The callbacks registered with `on_message` are called after the VALUE was set,
+    /// the callbacks registered with `on_rejected` if the message was rejected.
+    pub fn apply_message(
+        &self,
+        msg: &OscMessage,
+        source: ChangeSource,
+    ) -> Result<(), WriteRejection> {
+        self.apply_messages(std::slice::from_ref(msg), source)
+    }
+
+    /// apply the OSC messages of a bundle atomically, like `apply_message`:
+    /// either all messages are applied or, if any of them fails, none of them.
+    /// Other readers of the tree never see only a part of the messages applied.
+    pub fn apply_messages(
+        &self,
+        msgs: &[OscMessage],
+        source: ChangeSource,
+    ) -> Result<(), WriteRejection> {
+        let mut root = self.root.write().unwrap();
+        let checked = match msgs
+            .iter()
+            .map(|msg| check_message(&root, msg))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(checked) => checked,
+            Err(rejection) => {
+                drop(root);
+                let rejected = self.rejected.read().unwrap().0.clone();
+                for callback in rejected.iter() {
+                    callback(&rejection, &source);
+                }
+                return Err(rejection);
+            }
+        };
+        let mut changes = Vec::new();
+        for (path, value) in checked.into_iter().flatten() {
+            // the paths were resolved under the same lock, they can not be missing
+            let on_change = root
+                .store_checked(&path, value.clone())
+                .map_err(|_| WriteRejection::NotFound(path.clone()))?;
+            changes.push((path, value, on_change));
+        }
+        drop(root);
+
+        for (path, value, on_change) in changes {
+            self.notify(path, value, on_change, source);
+        }
+        let callbacks = self.callbacks.read().unwrap().0.clone();
+        for msg in msgs {
+            for callback in callbacks.iter() {
+                callback(msg);
+            }
+        }
+        Ok(())
+    }
+
+    /// call `callback` for every OSC message that was received and applied to the tree
+    /// Callbacks registered from within a callback are called from the next message on.
+    /// ```
+    /// use oscq_rs::{OSCNode, OscTreeHandle};
+    /// let tree = OscTreeHandle::new(OSCNode::root(None));
+    /// tree.on_message(|msg| println!("received {} {:?}", msg.addr, msg.args));
+    /// ```
+    pub fn on_message(&self, callback: impl Fn(&OscMessage) + Send + Sync + 'static) {
+        self.callbacks.write().unwrap().push(Arc::new(callback));
+    }
+
+    /// call `callback` for every write of a client that was rejected,
+    /// e.g. because the parameter is read only or the arguments do not match its TYPE
+    /// ```
+    /// use oscq_rs::{OSCNode, OscTreeHandle};
+    /// let tree = OscTreeHandle::new(OSCNode::root(None));
+    /// tree.on_rejected(|rejection, source| println!("rejected {:?}: {}", source, rejection));
+    /// ```
+    pub fn on_rejected(
+        &self,
+        callback: impl Fn(&WriteRejection, &ChangeSource) + Send + Sync + 'static,
+    ) {
+        self.rejected.write().unwrap().push(Arc::new(callback));
+    }
+
+    /// receive all changes made to the tree through this handle (or any of its clones)
+    /// from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<TreeEvent> {
+        self.events.subscribe()
+    }
+
+    /// send an event to all subscribers, it is fine if there are none
+    fn publish(&self, event: TreeEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// fill in the address of the OSC server in the HOST_INFO of the root,
+    /// values given by the user are kept
+    pub(crate) fn fill_host_info(&self, address: SocketAddr, transport: OscTransport) {
+        self.root
+            .write()
+            .unwrap()
+            .fill_host_info(address, transport);
+    }
+
+    /// the OSC bundles waiting for their timetag to be applied to the tree
+    pub(crate) fn bundles(&self) -> &BundleScheduler {
+        &self.bundles
+    }
+
+    /// lock the tree for reading.
+    /// The guard should not be held across `.await` points, since writers
+    /// (including the service) are blocked while it is alive.
+    pub fn read(&self) -> RwLockReadGuard<'_, OSCNode> {
+        self.root.read().unwrap()
+    }
+}
+
+/// resolve the parameters addressed by `msg` (an address or a pattern)
+/// and check its arguments and their ACCESS against each of them.
+/// Fails with the last rejection if the arguments can not be stored in any of them.
+fn check_message(
+    root: &OSCNode,
+    msg: &OscMessage,
+) -> Result<Vec<(String, Vec<OscType>)>, WriteRejection> {
+    let paths: Vec<String> = if is_osc_pattern(&msg.addr) {
+        root.match_pattern(&msg.addr)
+            .filter(|node| node.is_parameter())
+            .map(|node| node.full_path.clone())
+            .collect()
+    } else {
+        vec![msg.addr.clone()]
+    };
+
+    let mut rejection = WriteRejection::NotFound(msg.addr.clone());
+    let mut checked = Vec::new();
+    for path in paths {
+        match root.check_write(&path, msg.args.clone()) {
+            Ok(value) => checked.push((path, value)),
+            Err(err) => rejection = err,
+        }
+    }
+    if checked.is_empty() {
+        return Err(rejection);
+    }
+    Ok(checked)
+}
+
+/// the canonical spelling of an OSC address without empty parts,
+/// e.g. `/gain/` and `//gain` are both `/gain`
+pub(crate) fn normalize_path(path: &str) -> String {
+    let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    format!("/{}", parts.join("/"))
+}
+
+/// true if the (normalized) `path` is `prefix` itself or one of its sub nodes
+fn is_sub_path(path: &str, prefix: &str) -> bool {
+    prefix == "/"
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+impl From<OSCNode> for OscTreeHandle {
+    fn from(root: OSCNode) -> Self {
+        Self::new(root)
+    }
+}
+
+#[test]
+fn shared_tree_updates() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    let served = tree.clone();
+
+    assert!(served.read().get("/late".to_string()).is_err());
+
+    tree.add(OscQueryParameter::new(
+        "/late".to_string(),
+        OscType::Float(1.0),
+    ))
+    .unwrap();
+
+    let json = serde_json::to_string(&*served.read()).unwrap();
+    assert!(json.contains("\"FULL_PATH\":\"/late\""));
+}
+
+#[test]
+fn value_change_events() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(OscQueryParameter::new(
+        "/level".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    let mut events = tree.subscribe();
+
+    tree.set_value("/level".to_string(), vec![OscType::Float(0.5)])
+        .unwrap();
+    // rejected values are not published
+    assert!(tree
+        .set_value("/level".to_string(), vec![OscType::Int(1)])
+        .is_err());
+
+    assert_eq!(
+        events.try_recv().unwrap(),
+        TreeEvent::ValueChanged {
+            path: "/level".to_string(),
+            value: vec![OscType::Float(0.5)],
+            source: ChangeSource::Local,
+        }
+    );
+    assert!(events.try_recv().is_err());
+}
+
+#[test]
+fn path_change_events() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    let mut events = tree.subscribe();
+    let param = |path: &str| OscQueryParameter::new(path.to_string(), OscType::Float(0.0));
+
+    tree.add(param("/plugin/a/gain")).unwrap();
+    tree.add(param("/plugin/a/pan")).unwrap();
+    tree.add(param("/plugin/a/pan")).unwrap();
+    tree.replace(param("/plugin/a/gain")).unwrap();
+    tree.rename("/plugin/a".to_string(), "/plugin/b".to_string())
+        .unwrap();
+    tree.remove("/plugin/b/gain".to_string()).unwrap();
+    tree.remove("/plugin/b/pan".to_string()).unwrap();
+    tree.add(param("/master")).unwrap();
+    tree.clear();
+
+    let reset = |path: &str| TreeEvent::ValueChanged {
+        path: path.to_string(),
+        value: vec![OscType::Float(0.0)],
+        source: ChangeSource::Local,
+    };
+
+    let expected = [
+        TreeEvent::PathAdded("/plugin".to_string()),
+        TreeEvent::PathAdded("/plugin/a/pan".to_string()),
+        // replacing a parameter resets its value
+        TreeEvent::PathChanged("/plugin/a/pan".to_string()),
+        reset("/plugin/a/pan"),
+        TreeEvent::PathChanged("/plugin/a/gain".to_string()),
+        reset("/plugin/a/gain"),
+        TreeEvent::PathRenamed {
+            old: "/plugin/a".to_string(),
+            new: "/plugin/b".to_string(),
+        },
+        TreeEvent::PathRemoved("/plugin/b/gain".to_string()),
+        // the last parameter takes the now empty containers with it
+        TreeEvent::PathRemoved("/plugin".to_string()),
+        TreeEvent::PathAdded("/master".to_string()),
+        TreeEvent::PathRemoved("/master".to_string()),
+    ];
+    for event in expected {
+        assert_eq!(events.try_recv().unwrap(), event);
+    }
+    assert!(events.try_recv().is_err());
+}
+
+#[test]
+fn change_callbacks_and_watchers() {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let callback_changes = changes.clone();
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(
+        OscQueryParameter::new("/gain".to_string(), OscType::Float(0.0)).on_change(
+            move |value, source| {
+                callback_changes
+                    .lock()
+                    .unwrap()
+                    .push((value.to_vec(), *source))
+            },
+        ),
+    )
+    .unwrap();
+    let mut gain = tree.watch("/gain".to_string()).unwrap();
+    assert!(!gain.has_changed().unwrap());
+
+    let peer: SocketAddr = ([127, 0, 0, 1], 9000).into();
+    tree.set_value("/gain".to_string(), vec![OscType::Float(0.5)])
+        .unwrap();
+    tree.apply_message(
+        &OscMessage {
+            addr: "/gain".to_string(),
+            args: vec![OscType::Float(0.7)],
+        },
+        ChangeSource::Osc(peer),
+    )
+    .unwrap();
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        vec![
+            (vec![OscType::Float(0.5)], ChangeSource::Local),
+            (vec![OscType::Float(0.7)], ChangeSource::Osc(peer)),
+        ]
+    );
+    assert!(gain.has_changed().unwrap());
+    assert_eq!(
+        *gain.borrow_and_update(),
+        ValueChange {
+            value: vec![OscType::Float(0.7)],
+            source: ChangeSource::Osc(peer)
+        }
+    );
+    assert!(tree.watch("/missing".to_string()).is_err());
+}
+
+#[test]
+fn watchers_follow_the_tree() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    for path in ["/fx/gain", "/fx/pan", "/master"] {
+        tree.add(OscQueryParameter::new(
+            path.to_string(),
+            OscType::Float(0.0),
+        ))
+        .unwrap();
+    }
+    let mut gain = tree.watch("/fx/gain/".to_string()).unwrap();
+    let pan = tree.watch("/fx/pan".to_string()).unwrap();
+    let master = tree.watch("/master".to_string()).unwrap();
+
+    // a trailing slash addresses the same watcher
+    tree.set_value("/fx/gain".to_string(), vec![OscType::Float(0.5)])
+        .unwrap();
+    assert_eq!(gain.borrow_and_update().value, vec![OscType::Float(0.5)]);
+    assert_eq!(tree.watchers.lock().unwrap().len(), 3);
+
+    tree.rename("/fx".to_string(), "/bus".to_string()).unwrap();
+    tree.set_value("/bus/gain".to_string(), vec![OscType::Float(0.7)])
+        .unwrap();
+    assert_eq!(gain.borrow_and_update().value, vec![OscType::Float(0.7)]);
+
+    tree.remove("/bus/pan".to_string()).unwrap();
+    assert!(pan.has_changed().is_err());
+    assert!(gain.has_changed().is_ok());
+
+    tree.clear();
+    assert!(gain.has_changed().is_err());
+    assert!(master.has_changed().is_err());
+    assert!(tree.watchers.lock().unwrap().is_empty());
+}
+
+#[test]
+fn watchers_see_replaced_values() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    let gain = |value| OscQueryParameter::new("/gain".to_string(), OscType::Float(value));
+    tree.add(gain(0.0)).unwrap();
+    let mut watcher = tree.watch("/gain".to_string()).unwrap();
+    tree.set_value("/gain".to_string(), vec![OscType::Float(0.5)])
+        .unwrap();
+    assert_eq!(watcher.borrow_and_update().value, vec![OscType::Float(0.5)]);
+
+    tree.replace(gain(0.25)).unwrap();
+    assert!(watcher.has_changed().unwrap());
+    assert_eq!(
+        watcher.borrow_and_update().value,
+        vec![OscType::Float(0.25)]
+    );
+    tree.add(gain(0.75)).unwrap();
+    assert_eq!(
+        watcher.borrow_and_update().value,
+        vec![OscType::Float(0.75)]
+    );
+    assert_eq!(
+        tree.value("/gain".to_string()).unwrap(),
+        vec![OscType::Float(0.75)]
+    );
+}
+
+#[test]
+fn apply_message_patterns() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    for path in ["/mixer/ch1/gain", "/mixer/ch2/gain", "/mixer/ch3/pan"] {
+        tree.add(OscQueryParameter::new(
+            path.to_string(),
+            OscType::Float(0.0),
+        ))
+        .unwrap();
+    }
+    let msg = |addr: &str, args| OscMessage {
+        addr: addr.to_string(),
+        args,
+    };
+
+    tree.apply_message(
+        &msg("/mixer/*/gain", vec![OscType::Float(0.5)]),
+        ChangeSource::Local,
+    )
+    .unwrap();
+    for path in ["/mixer/ch1/gain", "/mixer/ch2/gain"] {
+        assert_eq!(
+            tree.value(path.to_string()).unwrap(),
+            vec![OscType::Float(0.5)]
+        );
+    }
+    assert_eq!(
+        tree.value("/mixer/ch3/pan".to_string()).unwrap(),
+        vec![OscType::Float(0.0)]
+    );
+    // containers matched by the pattern are not writable
+    assert!(tree
+        .apply_message(
+            &msg("/mix*", vec![OscType::Float(0.5)]),
+            ChangeSource::Local
+        )
+        .is_err());
+    assert!(tree
+        .apply_message(&msg("//gain", vec![OscType::Int(1)]), ChangeSource::Local)
+        .is_err());
+}
+
+#[test]
+fn apply_messages_atomically() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(OscQueryParameter::new(
+        "/a".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    tree.add(OscQueryParameter::new("/b".to_string(), OscType::Int(0)))
+        .unwrap();
+    let msg = |addr: &str, args| OscMessage {
+        addr: addr.to_string(),
+        args,
+    };
+
+    // the second message has the wrong type, so the first one is not applied either
+    let result = tree.apply_messages(
+        &[
+            msg("/a", vec![OscType::Float(1.0)]),
+            msg("/b", vec![OscType::Float(1.0)]),
+        ],
+        ChangeSource::Local,
+    );
+    assert!(result.is_err());
+    assert_eq!(
+        tree.value("/a".to_string()).unwrap(),
+        vec![OscType::Float(0.0)]
+    );
+
+    tree.apply_messages(
+        &[
+            msg("/a", vec![OscType::Float(1.0)]),
+            msg("/b", vec![OscType::Int(1)]),
+        ],
+        ChangeSource::Local,
+    )
+    .unwrap();
+    assert_eq!(
+        tree.value("/a".to_string()).unwrap(),
+        vec![OscType::Float(1.0)]
+    );
+    assert_eq!(tree.value("/b".to_string()).unwrap(), vec![OscType::Int(1)]);
+}
+
+#[test]
+fn register_callbacks_from_callbacks() {
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(OscQueryParameter::new("/a".to_string(), OscType::Int(0)))
+        .unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let inner_tree = tree.clone();
+    let inner_received = received.clone();
+    tree.on_message(move |msg| {
+        let received = inner_received.clone();
+        let value = msg.args.clone();
+        // registered while the callbacks are being called
+        inner_tree.on_message(move |_| received.lock().unwrap().push(value.clone()));
+    });
+    let inner_tree = tree.clone();
+    tree.on_rejected(move |_, _| inner_tree.on_rejected(|_, _| {}));
+    let msg = |args| OscMessage {
+        addr: "/a".to_string(),
+        args,
+    };
+
+    tree.apply_message(&msg(vec![OscType::Int(1)]), ChangeSource::Local)
+        .unwrap();
+    assert!(tree
+        .apply_message(&msg(vec![OscType::Float(1.0)]), ChangeSource::Local)
+        .is_err());
+    // the callback registered for the first message is called for the next one
+    tree.apply_message(&msg(vec![OscType::Int(2)]), ChangeSource::Local)
+        .unwrap();
+    assert_eq!(*received.lock().unwrap(), vec![vec![OscType::Int(1)]]);
+    assert_eq!(tree.callbacks.read().unwrap().0.len(), 3);
+    assert_eq!(tree.rejected.read().unwrap().0.len(), 2);
+}
+
+#[test]
+fn access_enforcement() {
+    use crate::OSCAccess;
+
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    for (path, access) in [
+        ("/meter", OSCAccess::Read),
+        ("/cue", OSCAccess::Write),
+        ("/hidden", OSCAccess::NoAcces),
+    ] {
+        tree.add(OscQueryParameter::new(path.to_string(), OscType::Int(1)).with_access(access))
+            .unwrap();
+    }
+    let rejected = Arc::new(Mutex::new(Vec::new()));
+    let hook_rejected = rejected.clone();
+    tree.on_rejected(move |rejection, _| hook_rejected.lock().unwrap().push(rejection.clone()));
+    let peer = ChangeSource::Osc(([127, 0, 0, 1], 9000).into());
+    let msg = |addr: &str, args| OscMessage {
+        addr: addr.to_string(),
+        args,
+    };
+
+    assert_eq!(
+        tree.apply_message(&msg("/meter", vec![OscType::Int(2)]), peer),
+        Err(WriteRejection::NotWritable {
+            path: "/meter".to_string(),
+            access: OSCAccess::Read
+        })
+    );
+    assert!(matches!(
+        tree.apply_message(&msg("/hidden", vec![OscType::Int(2)]), peer),
+        Err(WriteRejection::NotWritable { .. })
+    ));
+    assert!(matches!(
+        tree.apply_message(&msg("/cue", vec![OscType::Float(2.0)]), peer),
+        Err(WriteRejection::TypeMismatch { .. })
+    ));
+    assert_eq!(
+        tree.apply_message(&msg("/foo", vec![OscType::Int(2)]), peer),
+        Err(WriteRejection::NotFound("/foo".to_string()))
+    );
+    tree.apply_message(&msg("/cue", vec![OscType::Int(2)]), peer)
+        .unwrap();
+    let paths: Vec<_> = rejected
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|rejection| rejection.path().to_string())
+        .collect();
+    assert_eq!(paths, ["/meter", "/hidden", "/cue", "/foo"]);
+
+    // the application itself can still set read only values
+    tree.set_value("/meter".to_string(), vec![OscType::Int(3)])
+        .unwrap();
+    assert_eq!(
+        tree.value("/meter".to_string()).unwrap(),
+        vec![OscType::Int(3)]
+    );
+
+    // the values of write only parameters are not serialized
+    let json = serde_json::to_string(&*tree.read()).unwrap();
+    assert!(json.contains(r#""FULL_PATH":"/meter","ACCESS":1,"TYPE":"i","VALUE":[3]"#));
+    assert!(json.contains(r#""FULL_PATH":"/cue","ACCESS":2,"TYPE":"i"}"#));
+}
+
+#[test]
+fn coerce_client_arguments() {
+    use crate::CoercionPolicy;
+
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(OscQueryParameter::new(
+        "/strict".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    tree.add(
+        OscQueryParameter::new("/gain".to_string(), OscType::Float(0.0))
+            .with_coercion(CoercionPolicy::LenientNumeric),
+    )
+    .unwrap();
+    tree.add(
+        OscQueryParameter::new("/mute".to_string(), OscType::Bool(false))
+            .with_coercion(CoercionPolicy::LenientNumeric),
+    )
+    .unwrap();
+    tree.add(
+        OscQueryParameter::new("/label".to_string(), OscType::String("".to_string()))
+            .with_argument(crate::OscQueryArgument::new(OscType::Int(0)))
+            .with_coercion(CoercionPolicy::LenientAll),
+    )
+    .unwrap();
+    let rejected = Arc::new(Mutex::new(Vec::new()));
+    let hook_rejected = rejected.clone();
+    tree.on_rejected(move |rejection, _| hook_rejected.lock().unwrap().push(rejection.clone()));
+    let peer = ChangeSource::Osc(([127, 0, 0, 1], 9000).into());
+    let apply = |addr: &str, args| {
+        tree.apply_message(
+            &OscMessage {
+                addr: addr.to_string(),
+                args,
+            },
+            peer,
+        )
+    };
+    let value = |path: &str| tree.value(path.to_string()).unwrap();
+
+    apply("/gain", vec![OscType::Int(1)]).unwrap();
+    assert_eq!(value("/gain"), vec![OscType::Float(1.0)]);
+    apply("/mute", vec![OscType::Float(1.0)]).unwrap();
+    assert_eq!(value("/mute"), vec![OscType::Bool(true)]);
+    apply("/label", vec![OscType::Float(2.5), "7".into()]).unwrap();
+    assert_eq!(value("/label"), vec!["2.5".into(), OscType::Int(7)]);
+
+    // arguments that can not be converted are rejected and reported
+    assert_eq!(
+        apply("/strict", vec![OscType::Int(1)]),
+        Err(WriteRejection::TypeMismatch {
+            path: "/strict".to_string(),
+            args: vec![OscType::Int(1)]
+        })
+    );
+    assert!(apply("/gain", vec!["1".into()]).is_err());
+    assert!(apply("/label", vec![OscType::Int(1), "seven".into()]).is_err());
+    assert_eq!(rejected.lock().unwrap().len(), 3);
+    assert_eq!(value("/strict"), vec![OscType::Float(0.0)]);
+
+    // values out of the range of the TYPE and values that are not finite are not saturated
+    tree.add(
+        OscQueryParameter::new("/count".to_string(), OscType::Int(0))
+            .with_coercion(CoercionPolicy::LenientAll),
+    )
+    .unwrap();
+    tree.add(
+        OscQueryParameter::new("/level".to_string(), OscType::Double(0.0))
+            .with_coercion(CoercionPolicy::LenientNumeric),
+    )
+    .unwrap();
+    for (path, arg) in [
+        ("/count", OscType::Float(1e10)),
+        ("/count", OscType::Long(i64::MAX)),
+        ("/count", OscType::Float(f32::NAN)),
+        ("/count", "nan".into()),
+        ("/mute", OscType::Float(f32::NAN)),
+        ("/gain", OscType::Double(f64::INFINITY)),
+        ("/gain", OscType::Double(1e300)),
+        ("/gain", OscType::Long((1 << 24) + 1)),
+        ("/level", OscType::Long((1 << 53) + 1)),
+    ] {
+        assert!(
+            matches!(
+                apply(path, vec![arg.clone()]),
+                Err(WriteRejection::TypeMismatch { .. })
+            ),
+            "{:?} to {}",
+            arg,
+            path
+        );
+    }
+    apply("/count", vec![OscType::Long(-7)]).unwrap();
+    assert_eq!(value("/count"), vec![OscType::Int(-7)]);
+    apply("/level", vec![OscType::Long(1 << 53)]).unwrap();
+    assert_eq!(value("/level"), vec![OscType::Double(9007199254740992.0)]);
+
+    // the policy can be changed later, e.g. for a tree read from JSON
+    tree.set_coercion("/strict".to_string(), CoercionPolicy::LenientNumeric)
+        .unwrap();
+    apply("/strict", vec![OscType::Int(1)]).unwrap();
+    assert_eq!(value("/strict"), vec![OscType::Float(1.0)]);
+    assert!(tree
+        .set_coercion("/foo".to_string(), CoercionPolicy::LenientAll)
+        .is_err());
+}
@@ -1,10 +1,19 @@
+mod extended_type;
+mod handle;
 mod oscquery_types;
 mod oscunit;
+mod receiver;
+mod rejection;
 mod service;
 mod tokiort;
+mod websocket;
 
+pub use extended_type::*;
+pub use handle::*;
 pub use oscquery_types::*;
 pub use oscunit::*;
+pub use receiver::*;
+pub use rejection::*;
 pub use service::*;
 
 pub mod osc {
@@ -1,8 +1,12 @@
-use crate::OSCUnit;
+use crate::{ChangeSource, OSCExtendedType, OSCUnit, WriteRejection};
 use rosc::{OscError, OscType};
+use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::collections::{BTreeMap, VecDeque};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 /// options how to define a Range in OscQuery
@@ -15,16 +19,543 @@ enum OscRangeBounds {
     Discrete,
 }
 
+#[derive(Debug, Default, PartialEq)]
+/// RANGE of a single (non array) value, the bounds are of the same type as the value
+struct OscBounds {
+    min: Option<OscType>,
+    max: Option<OscType>,
+    vals: Option<Vec<OscType>>,
+}
+
+#[derive(Debug, PartialEq)]
+/// RANGE of a single argument,
+/// an array argument has a nested RANGE entry for each of its elements
+enum OscRange {
+    Array(Vec<OscRange>),
+    Bounds(OscBounds),
+    /// no RANGE for this argument (`null`), keeps the RANGE entries aligned with TYPE
+    Unbounded,
+}
+
+impl OscRange {
+    /// an empty range for a value of the type of `value`,
+    /// arrays get a range for each of their elements
+    fn for_value(value: &OscType) -> Self {
+        match value {
+            OscType::Array(a) => {
+                OscRange::Array(a.content.iter().map(OscRange::for_value).collect())
+            }
+            _ => OscRange::Bounds(OscBounds::default()),
+        }
+    }
+
+    /// call `f` with the bounds of every (array element) value and its type
+    fn update(&mut self, value: &OscType, f: &mut impl FnMut(&OscType, &mut OscBounds)) {
+        match (self, value) {
+            (OscRange::Array(ranges), OscType::Array(a)) => {
+                for (range, value) in ranges.iter_mut().zip(&a.content) {
+                    range.update(value, f);
+                }
+            }
+            (OscRange::Bounds(bounds), value) => f(value, bounds),
+            _ => {}
+        }
+    }
+
+    /// apply the `clipmode` to the `value`:
+    /// values outside of `MIN`/`MAX` are clamped, values that are not in `VALS` are rejected
+    fn clip(&self, value: OscType, clipmode: ClipMode) -> Result<OscType, String> {
+        if clipmode == ClipMode::None {
+            return Ok(value);
+        }
+        match (self, value) {
+            (OscRange::Array(ranges), OscType::Array(mut a)) => {
+                for (range, value) in ranges.iter().zip(a.content.iter_mut()) {
+                    *value = range.clip(value.clone(), clipmode)?;
+                }
+                Ok(OscType::Array(a))
+            }
+            (OscRange::Bounds(bounds), value) => {
+                if let Some(vals) = &bounds.vals {
+                    if !vals.contains(&value) {
+                        return Err(format!("{:?} is not one of the VALS {:?}", value, vals));
+                    }
+                }
+                let beyond = |bound: &Option<OscType>, ordering| {
+                    bound
+                        .clone()
+                        .filter(|bound| osc_partial_cmp(&value, bound) == Some(ordering))
+                };
+                let clipped = match clipmode {
+                    ClipMode::Low => beyond(&bounds.min, Ordering::Less),
+                    ClipMode::High => beyond(&bounds.max, Ordering::Greater),
+                    ClipMode::Both => beyond(&bounds.min, Ordering::Less)
+                        .or_else(|| beyond(&bounds.max, Ordering::Greater)),
+                    ClipMode::None => None,
+                };
+                Ok(clipped.unwrap_or(value))
+            }
+            (_, value) => Ok(value),
+        }
+    }
+}
+
+/// true if one part of an OSC address `pattern` (between two '/') matches the `name` of a node.
+/// The pattern is read once from left to right, keeping the positions in `name` that the
+/// pattern read so far can end at, so the time is bounded by the length of the pattern
+/// times the length of the name no matter how many `*` the pattern contains.
+fn osc_pattern_match(pattern: &[char], name: &[char]) -> bool {
+    // reachable[i]: the pattern read so far matches `name[..i]`
+    let mut reachable = vec![false; name.len() + 1];
+    reachable[0] = true;
+    let mut p = 0;
+    while p < pattern.len() {
+        let mut next = vec![false; name.len() + 1];
+        match pattern[p] {
+            '*' => {
+                // any number of characters after the first reachable position
+                if let Some(first) = reachable.iter().position(|r| *r) {
+                    next[first..].fill(true);
+                }
+                p += 1;
+            }
+            '[' => {
+                let Some(end) = pattern[p..].iter().position(|c| *c == ']') else {
+                    return false;
+                };
+                let end = p + end;
+                let (negate, set) = match &pattern[p + 1..end] {
+                    ['!', set @ ..] => (true, set),
+                    set => (false, set),
+                };
+                for (i, c) in name.iter().enumerate() {
+                    if reachable[i] && char_set_contains(set, *c) != negate {
+                        next[i + 1] = true;
+                    }
+                }
+                p = end + 1;
+            }
+            '{' => {
+                let Some(end) = pattern[p..].iter().position(|c| *c == '}') else {
+                    return false;
+                };
+                let end = p + end;
+                for choice in pattern[p + 1..end].split(|c| *c == ',') {
+                    for i in 0..=name.len() {
+                        if reachable[i] && name[i..].starts_with(choice) {
+                            next[i + choice.len()] = true;
+                        }
+                    }
+                }
+                p = end + 1;
+            }
+            c => {
+                for (i, n) in name.iter().enumerate() {
+                    if reachable[i] && (c == '?' || c == *n) {
+                        next[i + 1] = true;
+                    }
+                }
+                p += 1;
+            }
+        }
+        if !next.contains(&true) {
+            return false;
+        }
+        reachable = next;
+    }
+    reachable[name.len()]
+}
+
+/// true if the character set of a `[...]` pattern (e.g. `a-z0`) contains `c`
+fn char_set_contains(set: &[char], c: char) -> bool {
+    let mut i = 0;
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == '-' {
+            if (set[i]..=set[i + 2]).contains(&c) {
+                return true;
+            }
+            i += 3;
+        } else {
+            if set[i] == c {
+                return true;
+            }
+            i += 1;
+        }
+    }
+    false
+}
+
+/// true if the OSC address contains any of the pattern characters
+pub(crate) fn is_osc_pattern(addr: &str) -> bool {
+    addr.contains(['*', '?', '[', '{']) || addr.contains("//")
+}
+
+/// compare two OSC values of the same ordered type
+fn osc_partial_cmp(a: &OscType, b: &OscType) -> Option<Ordering> {
+    match (a, b) {
+        (OscType::Int(a), OscType::Int(b)) => a.partial_cmp(b),
+        (OscType::Long(a), OscType::Long(b)) => a.partial_cmp(b),
+        (OscType::Float(a), OscType::Float(b)) => a.partial_cmp(b),
+        (OscType::Double(a), OscType::Double(b)) => a.partial_cmp(b),
+        (OscType::Char(a), OscType::Char(b)) => a.partial_cmp(b),
+        (OscType::String(a), OscType::String(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
+
+impl Serialize for OscRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            OscRange::Array(ranges) => ranges.serialize(serializer),
+            OscRange::Unbounded => serializer.serialize_none(),
+            OscRange::Bounds(bounds) => {
+                let mut map = serializer.serialize_map(None)?;
+                if let Some(min) = &bounds.min {
+                    map.serialize_entry(&OscRangeBounds::Min, &OscValueRef(min))?;
+                }
+                if let Some(max) = &bounds.max {
+                    map.serialize_entry(&OscRangeBounds::Max, &OscValueRef(max))?;
+                }
+                if let Some(vals) = &bounds.vals {
+                    let vals: Vec<_> = vals.iter().map(OscValueRef).collect();
+                    map.serialize_entry(&OscRangeBounds::Discrete, &vals)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// true for the numeric types that can have a MIN and MAX given as numbers
+fn is_numeric(osc_type: &OscType) -> bool {
+    matches!(
+        osc_type,
+        OscType::Int(_) | OscType::Long(_) | OscType::Float(_) | OscType::Double(_)
+    )
+}
+
+/// convert a bound given for `value` (MIN, MAX or one of VALS) to the type of the value.
+/// Numbers are converted to the numeric type of the value like the arguments of clients,
+/// other bounds have to be of the type of the value.
+fn bound_as(value: &OscType, bound: &OscType) -> Result<OscType, OscError> {
+    if std::mem::discriminant(value) == std::mem::discriminant(bound) {
+        return Ok(bound.clone());
+    }
+    let number = match bound {
+        OscType::Int(i) => Some(Number::Integer(i64::from(*i))),
+        OscType::Long(l) => Some(Number::Integer(*l)),
+        OscType::Float(f) => Some(Number::Real(f64::from(*f))),
+        OscType::Double(d) => Some(Number::Real(*d)),
+        _ => None,
+    };
+    number
+        .filter(|_| is_numeric(value))
+        .and_then(|number| number.convert_to(value))
+        .ok_or_else(|| OscError::BadArg(format!("{:?} can not be a bound of {:?}", bound, value)))
+}
+
+/// closure called with the new VALUE of a parameter and the source of the change
+type ChangeFn = dyn Fn(&[OscType], &ChangeSource) + Send + Sync;
+
+/// callback for changes of the VALUE of a parameter, see `OscQueryParameter::on_change`
+#[derive(Clone)]
+pub(crate) struct ChangeCallback(Arc<ChangeFn>);
+
+impl ChangeCallback {
+    pub(crate) fn call(&self, value: &[OscType], source: &ChangeSource) {
+        (self.0)(value, source)
+    }
+}
+
+impl std::fmt::Debug for ChangeCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ChangeCallback")
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// How values outside of the RANGE of an argument are handled (CLIPMODE).
+/// The clipmode is applied by `OSCNode::set_value`, values that are not in `VALS`
+/// are rejected by every mode but `ClipMode::None`.
+pub enum ClipMode {
+    /// values are not clipped
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    /// values are clipped at the `MIN` of the range
+    #[serde(rename = "low")]
+    Low,
+    /// values are clipped at the `MAX` of the range
+    #[serde(rename = "high")]
+    High,
+    /// values are clipped at both ends of the range
+    #[serde(rename = "both")]
+    Both,
+}
+
+/// How the arguments of OSC messages received from clients are converted
+/// to the TYPE of a parameter before its VALUE is set.
+/// Arguments that can not be converted are rejected with `WriteRejection::TypeMismatch`,
+/// including numbers that are not finite or out of the range of the TYPE.
+/// The policy is set with `OscQueryParameter::with_coercion` or `OSCNode::set_coercion`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CoercionPolicy {
+    /// the arguments have to match the TYPE exactly
+    #[default]
+    Strict,
+    /// numbers and booleans are converted into each other,
+    /// e.g. an int to a float parameter or a float to a bool toggle (non zero is true)
+    LenientNumeric,
+    /// like `LenientNumeric`, values are converted to strings as well
+    /// and strings are parsed as numbers, booleans and chars
+    LenientAll,
+}
+
+impl CoercionPolicy {
+    /// convert `value` to the type of `osc_type`, `None` if that is not possible with this policy
+    fn coerce(self, osc_type: &OscType, value: OscType) -> Option<OscType> {
+        match (osc_type, value) {
+            (OscType::Array(types), OscType::Array(mut values)) => {
+                if types.content.len() != values.content.len() {
+                    return None;
+                }
+                values.content = types
+                    .content
+                    .iter()
+                    .zip(values.content)
+                    .map(|(osc_type, value)| self.coerce(osc_type, value))
+                    .collect::<Option<_>>()?;
+                Some(OscType::Array(values))
+            }
+            (osc_type, value)
+                if std::mem::discriminant(osc_type) == std::mem::discriminant(&value) =>
+            {
+                Some(value)
+            }
+            _ if self == CoercionPolicy::Strict => None,
+            (OscType::String(_), value) if self == CoercionPolicy::LenientAll => match value {
+                OscType::Int(i) => Some(i.to_string().into()),
+                OscType::Long(l) => Some(l.to_string().into()),
+                OscType::Float(f) => Some(f.to_string().into()),
+                OscType::Double(d) => Some(d.to_string().into()),
+                OscType::Bool(b) => Some(b.to_string().into()),
+                OscType::Char(c) => Some(c.to_string().into()),
+                _ => None,
+            },
+            (OscType::Char(_), OscType::String(s)) if self == CoercionPolicy::LenientAll => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(OscType::Char(c)),
+                    _ => None,
+                }
+            }
+            (osc_type, value) => {
+                let number = match value {
+                    OscType::Int(i) => Number::Integer(i64::from(i)),
+                    OscType::Long(l) => Number::Integer(l),
+                    OscType::Float(f) => Number::Real(f64::from(f)),
+                    OscType::Double(d) => Number::Real(d),
+                    OscType::Bool(b) => Number::Integer(i64::from(b)),
+                    OscType::String(s) if self == CoercionPolicy::LenientAll => match s.trim() {
+                        "true" => Number::Integer(1),
+                        "false" => Number::Integer(0),
+                        s => match s.parse() {
+                            Ok(i) => Number::Integer(i),
+                            Err(_) => Number::Real(s.parse().ok()?),
+                        },
+                    },
+                    _ => return None,
+                };
+                number.convert_to(osc_type)
+            }
+        }
+    }
+}
+
+/// a number received for a numeric or bool parameter
+enum Number {
+    Integer(i64),
+    Real(f64),
+}
+
+impl Number {
+    /// convert the number to the type of `osc_type`, integers are rounded.
+    /// `None` if the number is not finite, out of the range of the type,
+    /// or an integer that can not be represented exactly as float.
+    fn convert_to(self, osc_type: &OscType) -> Option<OscType> {
+        if let Number::Real(r) = self {
+            if !r.is_finite() {
+                return None;
+            }
+        }
+        match (osc_type, self) {
+            (OscType::Int(_), Number::Integer(i)) => i32::try_from(i).ok().map(OscType::Int),
+            (OscType::Int(_), Number::Real(r)) => {
+                let r = r.round();
+                (r >= f64::from(i32::MIN) && r <= f64::from(i32::MAX))
+                    .then_some(OscType::Int(r as i32))
+            }
+            (OscType::Long(_), Number::Integer(i)) => Some(OscType::Long(i)),
+            (OscType::Long(_), Number::Real(r)) => {
+                // i64::MAX is not a f64, 2^63 is the first value out of range
+                let r = r.round();
+                (r >= i64::MIN as f64 && r < -(i64::MIN as f64)).then_some(OscType::Long(r as i64))
+            }
+            (OscType::Float(_), Number::Integer(i)) => {
+                let f = i as f32;
+                (f as i128 == i128::from(i)).then_some(OscType::Float(f))
+            }
+            (OscType::Float(_), Number::Real(r)) => {
+                let f = r as f32;
+                f.is_finite().then_some(OscType::Float(f))
+            }
+            (OscType::Double(_), Number::Integer(i)) => {
+                let d = i as f64;
+                (d as i128 == i128::from(i)).then_some(OscType::Double(d))
+            }
+            (OscType::Double(_), Number::Real(r)) => Some(OscType::Double(r)),
+            (OscType::Bool(_), Number::Integer(i)) => Some(OscType::Bool(i != 0)),
+            (OscType::Bool(_), Number::Real(r)) => Some(OscType::Bool(r != 0.0)),
+            _ => None,
+        }
+    }
+}
+
+/// OscQueryArgument describes one argument of an OSC message,
+/// a `OscQueryParameter` has one or more of them.
+#[derive(Debug)]
+pub struct OscQueryArgument {
+    value: OscType,                         // value&type description
+    range: Option<OscRange>,                // value range description
+    unit: Option<OSCUnit>,                  // unit description
+    clipmode: Option<ClipMode>,             // clipmode description
+    extended_type: Option<OSCExtendedType>, // semantic type description
+}
+
+impl OscQueryArgument {
+    /// Create a new `OscQueryArgument` with the given default `value`, the value also defines the TYPE.
+    /// ```
+    /// use oscq_rs::OscQueryArgument;
+    /// let argument = OscQueryArgument::new(rosc::OscType::Float(1.0)).with_min_max(0.0, 1.0);
+    /// println!("{:?}",argument);
+    /// ```
+    pub fn new(value: OscType) -> Self {
+        Self {
+            value,
+            range: None,
+            unit: None,
+            clipmode: None,
+            extended_type: None,
+        }
+    }
+
+    /// Set the `unit` of the argument
+    pub fn with_unit(mut self, unit: OSCUnit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Set the `clipmode` of the argument
+    pub fn with_clipmode(mut self, clipmode: ClipMode) -> Self {
+        self.clipmode = Some(clipmode);
+        self
+    }
+
+    /// Set the `extended_type` of the argument
+    pub fn with_extended_type(mut self, extended_type: OSCExtendedType) -> Self {
+        self.extended_type = Some(extended_type);
+        self
+    }
+
+    /// change the bounds of the `range`, for array values `f` is called for every element
+    fn update_range(mut self, mut f: impl FnMut(&OscType, &mut OscBounds)) -> Self {
+        self.range
+            .get_or_insert_with(|| OscRange::for_value(&self.value))
+            .update(&self.value, &mut f);
+        self
+    }
+
+    /// like `update_range`, fails with the first error returned by `f`
+    fn try_update_range(
+        self,
+        mut f: impl FnMut(&OscType, &mut OscBounds) -> Result<(), OscError>,
+    ) -> Result<Self, OscError> {
+        let mut result = Ok(());
+        let argument = self.update_range(|value, bounds| {
+            if result.is_ok() {
+                result = f(value, bounds);
+            }
+        });
+        result.map(|()| argument)
+    }
+
+    /// Set the `min` and `max` values for the `range` of the argument.
+    /// The bounds are converted to the numeric type of the value (e.g. `MIN` is an integer for an Int value),
+    /// values that are not numbers (e.g. strings) get no `MIN` and `MAX`.
+    /// For array values the range is applied to every element of the array.
+    pub fn with_min_max(self, min: f32, max: f32) -> Self {
+        self.update_range(|value, bounds| {
+            if is_numeric(value) {
+                bounds.min = Number::Real(f64::from(min)).convert_to(value);
+                bounds.max = Number::Real(f64::from(max)).convert_to(value);
+            }
+        })
+    }
+
+    /// Set only the `min` value for the `range` of the argument.
+    /// A numeric bound is converted to the numeric type of the value,
+    /// fails with `OscError::BadArg` if it can not be converted or is of another, non numeric type.
+    pub fn with_min(self, min: impl Into<OscType>) -> Result<Self, OscError> {
+        let min = min.into();
+        self.try_update_range(|value, bounds| {
+            bounds.min = Some(bound_as(value, &min)?);
+            Ok(())
+        })
+    }
+
+    /// Set only the `max` value for the `range` of the argument.
+    /// A numeric bound is converted to the numeric type of the value,
+    /// fails with `OscError::BadArg` if it can not be converted or is of another, non numeric type.
+    pub fn with_max(self, max: impl Into<OscType>) -> Result<Self, OscError> {
+        let max = max.into();
+        self.try_update_range(|value, bounds| {
+            bounds.max = Some(bound_as(value, &max)?);
+            Ok(())
+        })
+    }
+
+    /// Set the list of allowed values (`VALS`) for the `range` of the argument.
+    /// Numeric values are converted to the numeric type of the value like in `with_min`,
+    /// fails with `OscError::BadArg` if one of them can not be converted.
+    pub fn with_vals<T: Into<OscType>>(
+        self,
+        vals: impl IntoIterator<Item = T>,
+    ) -> Result<Self, OscError> {
+        let vals: Vec<OscType> = vals.into_iter().map(Into::into).collect();
+        self.try_update_range(|value, bounds| {
+            bounds.vals = Some(
+                vals.iter()
+                    .map(|val| bound_as(value, val))
+                    .collect::<Result<_, _>>()?,
+            );
+            Ok(())
+        })
+    }
+}
+
 /// OscQueryParameter describes a single OSC Value for use in the OSCQuery Protocol
-/// the OSCQuery Protocol adds a more detailed description to the OSC Value
+/// the OSCQuery Protocol adds a more detailed description to the OSC Value.
+/// An OSC Value can consist of multiple arguments (e.g. `/color ffff`), each described by a `OscQueryArgument`.
 #[derive(Debug)]
 pub struct OscQueryParameter {
-    description: String,                          // short description of the Value
-    address: String,                              // OSC address/path of the value
-    value: OscType,                               // value&type description
-    access: Option<OSCAccess>,                    // access rights description
-    range: Option<BTreeMap<OscRangeBounds, f32>>, // value range description
-    unit: Option<OSCUnit>,                        // unit description
+    description: String,               // short description of the Value
+    address: String,                   // OSC address/path of the value
+    arguments: Vec<OscQueryArgument>,  // type, value, range... of each argument
+    access: Option<OSCAccess>,         // access rights description
+    tags: Vec<String>,                 // tags for grouping/searching parameters
+    critical: bool,                    // changes need reliable delivery
+    coercion: CoercionPolicy,          // conversion of the arguments of clients
+    on_change: Option<ChangeCallback>, // called when the value changed
 }
 
 impl OscQueryParameter {
@@ -38,11 +569,66 @@ impl OscQueryParameter {
         Self {
             description: "".to_string(),
             address: addr,
-            value,
+            arguments: vec![OscQueryArgument::new(value)],
             access: None,
-            range: None,
-            unit: None,
+            tags: Vec::new(),
+            critical: false,
+            coercion: CoercionPolicy::Strict,
+            on_change: None,
+        }
+    }
+
+    /// Create a new `OscQueryParameter` for an array of values, with the TYPE "[...]".
+    /// ```
+    /// use oscq_rs::OscQueryParameter;
+    /// use rosc::OscType;
+    /// let parameter = OscQueryParameter::array("/mixer/eq".to_string(), vec![OscType::Float(0.0); 8])
+    ///                 .with_min_max(-12.0, 12.0);
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn array(addr: String, values: Vec<OscType>) -> Self {
+        Self::new(addr, OscType::Array(rosc::OscArray { content: values }))
+    }
+
+    /// The OSC address/path of the parameter
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Add another argument to the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// The `with_unit`, `with_min_max`... methods of the parameter apply to the last added argument.
+    /// ```
+    /// use oscq_rs::{OscQueryArgument, OscQueryParameter};
+    /// use rosc::OscType;
+    /// let parameter = OscQueryParameter::new("/color".to_string(), OscType::Float(1.0))
+    ///                 .with_min_max(0.0, 1.0)
+    ///                 .with_argument(OscQueryArgument::new(OscType::Float(0.5)).with_min_max(0.0, 1.0))
+    ///                 .with_argument(OscQueryArgument::new(OscType::Float(0.0)))
+    ///                 .with_min_max(0.0, 1.0);
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_argument(mut self, argument: OscQueryArgument) -> Self {
+        self.arguments.push(argument);
+        self
+    }
+
+    /// apply a builder method of `OscQueryArgument` to the last argument
+    fn update_last(mut self, f: impl FnOnce(OscQueryArgument) -> OscQueryArgument) -> Self {
+        if let Some(argument) = self.arguments.pop() {
+            self.arguments.push(f(argument));
+        }
+        self
+    }
+
+    /// apply a fallible builder method of `OscQueryArgument` to the last argument
+    fn try_update_last(
+        mut self,
+        f: impl FnOnce(OscQueryArgument) -> Result<OscQueryArgument, OscError>,
+    ) -> Result<Self, OscError> {
+        if let Some(argument) = self.arguments.pop() {
+            self.arguments.push(f(argument)?);
         }
+        Ok(self)
     }
 
     /// Set the `access` for the `OscQueryParameter` and return a new `OscQueryParameter` instance.
@@ -57,30 +643,158 @@ impl OscQueryParameter {
         self
     }
 
-    /// Set the `unit` for the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// Set the `unit` (of the last argument) for the `OscQueryParameter` and return a new `OscQueryParameter` instance.
     /// ```
     /// use oscq_rs::{OscQueryParameter,OSCUnit,OSCDistance};
     /// let parameter = OscQueryParameter::new("/test/param".to_string(), rosc::OscType::Int(42))
     ///                 .with_unit(OSCUnit::Distance(OSCDistance::Meter));
     /// println!("{:?}",parameter);
     /// ```
-    pub fn with_unit(mut self, unit: OSCUnit) -> Self {
-        self.unit = Some(unit);
-        self
+    pub fn with_unit(self, unit: OSCUnit) -> Self {
+        self.update_last(|argument| argument.with_unit(unit))
+    }
+
+    /// Set the `clipmode` (of the last argument) for the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// ```
+    /// use oscq_rs::{ClipMode, OscQueryParameter};
+    /// let parameter = OscQueryParameter::new("/test/param".to_string(), rosc::OscType::Int(42))
+    ///                 .with_min_max(0.0, 100.0)
+    ///                 .with_clipmode(ClipMode::Both);
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_clipmode(self, clipmode: ClipMode) -> Self {
+        self.update_last(|argument| argument.with_clipmode(clipmode))
+    }
+
+    /// Set the `extended_type` (of the last argument) for the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// ```
+    /// use oscq_rs::{OSCExtendedType, OscQueryParameter};
+    /// let parameter = OscQueryParameter::new("/media/file".to_string(), "".into())
+    ///                 .with_extended_type(OSCExtendedType::FilePath);
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_extended_type(self, extended_type: OSCExtendedType) -> Self {
+        self.update_last(|argument| argument.with_extended_type(extended_type))
     }
 
-    /// Set the `min` and `max` values for the `range` of the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// Set the `min` and `max` values for the `range` (of the last argument) of the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// The bounds are converted to the numeric type of the value (e.g. `MIN` is an integer for an Int value),
+    /// values that are not numbers (e.g. strings) get no `MIN` and `MAX`.
+    /// For array values the range is applied to every element of the array.
     /// ```
     /// use oscq_rs::OscQueryParameter;
     /// let parameter = OscQueryParameter::new("/test/param".to_string(), rosc::OscType::Int(42))
     ///                 .with_min_max(0.0, 100.0);;
     /// println!("{:?}",parameter);
     /// ```
-    pub fn with_min_max(mut self, min: f32, max: f32) -> Self {
-        let mut range = BTreeMap::new();
-        range.insert(OscRangeBounds::Min, min);
-        range.insert(OscRangeBounds::Max, max);
-        self.range = Some(range);
+    pub fn with_min_max(self, min: f32, max: f32) -> Self {
+        self.update_last(|argument| argument.with_min_max(min, max))
+    }
+
+    /// Set only the `min` value for the `range` (of the last argument) of the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// A numeric bound is converted to the numeric type of the value,
+    /// fails with `OscError::BadArg` if it can not be converted or is of another, non numeric type.
+    /// ```
+    /// use oscq_rs::OscQueryParameter;
+    /// let parameter = OscQueryParameter::new("/test/param".to_string(), rosc::OscType::Long(42))
+    ///                 .with_min(0)
+    ///                 .unwrap();
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_min(self, min: impl Into<OscType>) -> Result<Self, OscError> {
+        self.try_update_last(|argument| argument.with_min(min))
+    }
+
+    /// Set only the `max` value for the `range` (of the last argument) of the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// A numeric bound is converted to the numeric type of the value,
+    /// fails with `OscError::BadArg` if it can not be converted or is of another, non numeric type.
+    /// ```
+    /// use oscq_rs::OscQueryParameter;
+    /// let parameter = OscQueryParameter::new("/test/param".to_string(), rosc::OscType::Double(0.5))
+    ///                 .with_max(1.0f64)
+    ///                 .unwrap();
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_max(self, max: impl Into<OscType>) -> Result<Self, OscError> {
+        self.try_update_last(|argument| argument.with_max(max))
+    }
+
+    /// Set the list of allowed values (`VALS`) for the `range` (of the last argument) of the `OscQueryParameter`
+    /// and return a new `OscQueryParameter` instance. Used to enumerate options like blend modes.
+    /// Numeric values are converted to the numeric type of the value like in `with_min`,
+    /// fails with `OscError::BadArg` if one of them can not be converted.
+    /// ```
+    /// use oscq_rs::OscQueryParameter;
+    /// let parameter = OscQueryParameter::new("/layer/blend".to_string(), "normal".into())
+    ///                 .with_vals(["normal", "add", "multiply"])
+    ///                 .unwrap();
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_vals<T: Into<OscType>>(
+        self,
+        vals: impl IntoIterator<Item = T>,
+    ) -> Result<Self, OscError> {
+        self.try_update_last(|argument| argument.with_vals(vals))
+    }
+
+    /// Add the `tags` (`TAGS`) to the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// ```
+    /// use oscq_rs::OscQueryParameter;
+    /// let parameter = OscQueryParameter::new("/light/dimmer".to_string(), rosc::OscType::Float(0.0))
+    ///                 .with_tags(["lighting", "scene-3"]);
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_tags<T: Into<String>>(mut self, tags: impl IntoIterator<Item = T>) -> Self {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add a single `tag` (`TAGS`) to the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    pub fn with_tag(self, tag: impl Into<String>) -> Self {
+        self.with_tags([tag])
+    }
+
+    /// Mark the `OscQueryParameter` as `critical` (`CRITICAL`) and return a new `OscQueryParameter` instance.
+    /// Changes of critical parameters are always streamed over the WebSocket,
+    /// even to clients that receive the other values over UDP.
+    /// ```
+    /// use oscq_rs::OscQueryParameter;
+    /// let parameter = OscQueryParameter::new("/scene/go".to_string(), rosc::OscType::Int(0))
+    ///                 .with_critical(true);
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_critical(mut self, critical: bool) -> Self {
+        self.critical = critical;
+        self
+    }
+
+    /// Set the `CoercionPolicy` used to convert the arguments of OSC messages from clients
+    /// to the TYPE of the `OscQueryParameter` and return a new `OscQueryParameter` instance.
+    /// ```
+    /// use oscq_rs::{CoercionPolicy, OscQueryParameter};
+    /// // accept 0/1 ints and floats for a toggle
+    /// let parameter = OscQueryParameter::new("/mixer/mute".to_string(), rosc::OscType::Bool(false))
+    ///                 .with_coercion(CoercionPolicy::LenientNumeric);
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn with_coercion(mut self, coercion: CoercionPolicy) -> Self {
+        self.coercion = coercion;
+        self
+    }
+
+    /// Call `callback` with the new value and the source of the change whenever the VALUE of the parameter is set,
+    /// by local code, incoming OSC or a WebSocket client. Return a new `OscQueryParameter` instance.
+    /// ```
+    /// use oscq_rs::OscQueryParameter;
+    /// let parameter = OscQueryParameter::new("/mixer/gain".to_string(), rosc::OscType::Float(0.0))
+    ///                 .on_change(|value, source| println!("{:?} from {:?}", value, source));
+    /// println!("{:?}",parameter);
+    /// ```
+    pub fn on_change(
+        mut self,
+        callback: impl Fn(&[OscType], &ChangeSource) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_change = Some(ChangeCallback(Arc::new(callback)));
         self
     }
 
@@ -97,7 +811,7 @@ impl OscQueryParameter {
     }
 }
 
-#[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize_repr, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 /// description of the OSCQuery Access Rules
 pub enum OSCAccess {
@@ -107,43 +821,124 @@ pub enum OSCAccess {
     ReadWrite = 3,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Transport protocol of the OSC server (OSC_TRANSPORT)
+pub enum OscTransport {
+    #[serde(rename = "UDP")]
+    Udp,
+    #[serde(rename = "TCP")]
+    Tcp,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// How OSC packets are separated in a TCP stream
+pub enum OscFraming {
+    /// OSC 1.0: every packet is preceded by its size as 32 bit big endian integer
+    LengthPrefix,
+    /// OSC 1.1: every packet is enclosed in SLIP END bytes (double END)
+    #[default]
+    Slip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// OSCQuery Host Information Section
 pub struct OscHostInfo {
     #[serde(rename = "NAME")]
     name: String, // OSC device name
-    #[serde(rename = "OSC_IP")]
-    osc_ip: String, // IP of the OSC device
-    #[serde(rename = "OSC_PORT")]
-    osc_port: u16, // Port of the OSC device
-    #[serde(rename = "OSC_TRANSPORT")]
-    osc_trans: String, // IP of the OSC device
+    #[serde(rename = "OSC_IP", default, skip_serializing_if = "Option::is_none")]
+    osc_ip: Option<String>, // IP of the OSC device
+    #[serde(rename = "OSC_PORT", default, skip_serializing_if = "Option::is_none")]
+    osc_port: Option<u16>, // Port of the OSC device
+    #[serde(
+        rename = "OSC_TRANSPORT",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    osc_trans: Option<OscTransport>, // Transport protocol of the OSC device
+    #[serde(skip)]
+    framing: OscFraming, // Framing of OSC packets over TCP
     #[serde(rename = "EXTENSIONS")]
     extension: OscHostInfoExtension, // Information of the supported OSCQuery extension
+    #[serde(skip)]
+    auto_extensions: bool, // enable the extensions of the attributes used in the tree
 }
 
 impl OscHostInfo {
     /// create new HostInformation
     pub fn new(device_name: String, osc_ip: String, osc_port: u16) -> Self {
+        OscHostInfo {
+            osc_ip: Some(osc_ip),
+            osc_port: Some(osc_port),
+            osc_trans: Some(OscTransport::Udp),
+            ..Self::from_name(device_name)
+        }
+    }
+    /// create new HostInformation with only a name,
+    /// the OSC IP, port and transport are filled in with the address `run_osc_receiver` is bound to.
+    /// Without a receiver they are left out, clients then send OSC to the OSCQuery server.
+    /// ```
+    /// use oscq_rs::{OSCNode, OscHostInfo};
+    /// let root = OSCNode::root(Some(Box::new(OscHostInfo::from_name("My OSC Server".to_string()))));
+    /// ```
+    pub fn from_name(device_name: String) -> Self {
         OscHostInfo {
             name: device_name,
-            osc_ip,
-            osc_port,
-            osc_trans: "UDP".to_string(),
+            osc_ip: None,
+            osc_port: None,
+            osc_trans: None,
+            framing: OscFraming::default(),
             extension: OscHostInfoExtension::default(),
+            auto_extensions: false,
+        }
+    }
+    /// fill in the OSC IP, port (or port 0) and transport that were not given by the user.
+    /// An unspecified IP (e.g. "0.0.0.0") is left out, clients then use the IP of the OSCQuery server.
+    pub(crate) fn fill_address(&mut self, address: SocketAddr, transport: OscTransport) {
+        if self.osc_ip.is_none() && !address.ip().is_unspecified() {
+            self.osc_ip = Some(address.ip().to_string());
+        }
+        if matches!(self.osc_port, None | Some(0)) {
+            self.osc_port = Some(address.port());
         }
+        self.osc_trans.get_or_insert(transport);
+    }
+    /// serve OSC over TCP (`OSC_TRANSPORT: "TCP"`) with the given `framing` of the packets
+    /// ```
+    /// use oscq_rs::{OscFraming, OscHostInfo};
+    /// let info = OscHostInfo::new("OSCQuery Test".to_string(), "127.0.0.1".to_string(), 6666)
+    ///     .with_tcp(OscFraming::Slip);
+    /// ```
+    pub fn with_tcp(mut self, framing: OscFraming) -> Self {
+        self.osc_trans = Some(OscTransport::Tcp);
+        self.framing = framing;
+        self
+    }
+    /// enable the extensions of all attributes (VALUE, RANGE, UNIT...) that are used by any node in the tree,
+    /// in addition to the extensions enabled with the `with_ext_*` methods.
+    /// ```
+    /// use oscq_rs::{OSCNode, OscHostInfo, OscQueryParameter};
+    /// let info = OscHostInfo::new("OSCQuery Test".to_string(), "127.0.0.1".to_string(), 6666)
+    ///     .with_auto_extensions();
+    /// let mut root = OSCNode::root(Some(Box::new(info)));
+    /// root.add(OscQueryParameter::new("/test".to_string(), rosc::OscType::Int(1)).with_min_max(0.0, 10.0))
+    ///     .unwrap();
+    /// assert!(serde_json::to_string(&root).unwrap().contains("\"RANGE\":true"));
+    /// ```
+    pub fn with_auto_extensions(mut self) -> Self {
+        self.auto_extensions = true;
+        self
     }
     /// enable access extension
     pub fn with_ext_access(mut self) -> Self {
         self.extension.access = true;
         self
     }
-    /// enable clipmode extension (WIP)
+    /// enable clipmode extension
     pub fn with_ext_clipmode(mut self) -> Self {
         self.extension.clipmode = true;
         self
     }
-    /// enable critical extension (WIP)
+    /// enable critical extension (CRITICAL values are always sent over the WebSocket)
     pub fn with_ext_critical(mut self) -> Self {
         self.extension.critical = true;
         self
@@ -153,17 +948,17 @@ impl OscHostInfo {
         self.extension.description = true;
         self
     }
-    /// enable extended type extension (WIP)
+    /// enable extended type extension
     pub fn with_ext_extended_type(mut self) -> Self {
         self.extension.extended_type = true;
         self
     }
-    /// enable listen extension (WIP)
+    /// enable listen extension (LISTEN/IGNORE over the WebSocket on the OSCQuery port)
     pub fn with_ext_listen(mut self) -> Self {
         self.extension.listen = true;
         self
     }
-    /// enable path changed extension (WIP)
+    /// enable path changed extension (PATH_ADDED/PATH_REMOVED/PATH_RENAMED/PATH_CHANGED over the WebSocket)
     pub fn with_ext_path_changed(mut self) -> Self {
         self.extension.path_changed = true;
         self
@@ -173,7 +968,7 @@ impl OscHostInfo {
         self.extension.range = true;
         self
     }
-    /// enable tags extension (WIP)
+    /// enable tags extension
     pub fn with_ext_tags(mut self) -> Self {
         self.extension.tags = true;
         self
@@ -190,9 +985,9 @@ impl OscHostInfo {
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 /// Collection of the Available and Unavailable OSC Extension
-struct OscHostInfoExtension {
+pub(crate) struct OscHostInfoExtension {
     #[serde(rename = "ACCESS")]
     access: bool,
     #[serde(rename = "VALUE")]
@@ -217,40 +1012,77 @@ struct OscHostInfoExtension {
     path_changed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-/// Representation of a Node in the OSCQuery tree data structure
-/// This struct can be serialized into a JSON string.
-/// This JSON then will follow the OSCQuery protocol
-pub struct OSCNode {
-    #[serde(rename = "DESCRIPTION")]
-    description: String,
-    #[serde(rename = "FULL_PATH")]
-    full_path: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "ACCESS")]
-    access: Option<OSCAccess>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "CONTENTS")]
-    contents: Option<BTreeMap<String, OSCNode>>,
-    #[serde(serialize_with = "osc_type_serialize")]
-    #[serde(deserialize_with = "osc_type_deserialize")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "TYPE")]
+impl OscHostInfoExtension {
+    /// true if clients are notified about changes of the namespace (PATH_CHANGED)
+    pub(crate) fn path_changed(&self) -> bool {
+        self.path_changed
+    }
+
+    /// true if clients can LISTEN to the values of nodes, which needs the LISTEN and VALUE extensions
+    pub(crate) fn listen(&self) -> bool {
+        self.listen && self.value
+    }
+
+    /// true if the attribute (e.g. "RANGE") can be queried with these extensions,
+    /// `None` for attributes that are not known at all
+    pub(crate) fn attribute_enabled(&self, attribute: &str) -> Option<bool> {
+        match attribute {
+            "FULL_PATH" | "CONTENTS" | "TYPE" | "HOST_INFO" => Some(true),
+            "ACCESS" => Some(self.access),
+            "VALUE" => Some(self.value),
+            "RANGE" => Some(self.range),
+            "DESCRIPTION" => Some(self.description),
+            "TAGS" => Some(self.tags),
+            "EXTENDED_TYPE" => Some(self.extended_type),
+            "UNIT" => Some(self.unit),
+            "CRITICAL" => Some(self.critical),
+            "CLIPMODE" => Some(self.clipmode),
+            _ => None,
+        }
+    }
+
+    /// all extensions enabled
+    fn all() -> Self {
+        Self {
+            access: true,
+            value: true,
+            range: true,
+            description: true,
+            tags: true,
+            extended_type: true,
+            unit: true,
+            critical: true,
+            clipmode: true,
+            listen: true,
+            path_changed: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "OSCNodeRepr")]
+/// Representation of a Node in the OSCQuery tree data structure
+/// This struct can be serialized into a JSON string.
+/// This JSON then will follow the OSCQuery protocol,
+/// only the attributes of the extensions enabled in the HOST_INFO of the node are included.
+/// The `CoercionPolicy` of a parameter is not part of the JSON,
+/// nodes read from JSON are `CoercionPolicy::Strict` until `set_coercion` is called.
+pub struct OSCNode {
+    description: String,
+    pub(crate) full_path: String,
+    access: Option<OSCAccess>,
+    pub(crate) contents: Option<BTreeMap<String, OSCNode>>,
     osc_type: Option<Vec<OscType>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "VALUE")]
-    #[serde(serialize_with = "osc_value_serialize")]
-    #[serde(deserialize_with = "osc_value_deserialize")]
     value: Option<Vec<OscType>>,
-    #[serde(rename = "RANGE")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    range: Option<Vec<BTreeMap<OscRangeBounds, f32>>>,
-    #[serde(rename = "UNIT")]
-    #[serde(skip_serializing_if = "Option::is_none")]
-    unit: Option<Vec<OSCUnit>>,
-    #[serde(rename = "HOST_INFO")]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<Vec<OscRange>>,
+    unit: Option<Vec<Option<OSCUnit>>>,
+    clipmode: Option<Vec<ClipMode>>,
+    extended_type: Option<Vec<Option<OSCExtendedType>>>,
+    tags: Option<Vec<String>>,
+    critical: Option<bool>,
     host_info: Option<Box<OscHostInfo>>,
+    coercion: CoercionPolicy,
+    on_change: Option<ChangeCallback>,
 }
 
 impl OSCNode {
@@ -270,99 +1102,406 @@ impl OSCNode {
             value: None,
             range: None,
             unit: None,
+            clipmode: None,
+            extended_type: None,
+            tags: None,
+            critical: None,
+            coercion: CoercionPolicy::Strict,
+            on_change: None,
             host_info,
         }
     }
 
-    /// walks down the tree, creates new nodes if necessary,
-    /// and insert the Parament at the Position defined by its address
-    fn add_recursion(
-        &mut self,
-        parameter: OscQueryParameter,
-        mut addr: VecDeque<String>,
-    ) -> Result<(), OscError> {
+    /// create an empty container node at the given path
+    fn container(full_path: String) -> Self {
+        Self {
+            description: "".to_string(),
+            full_path,
+            access: Some(OSCAccess::NoAcces),
+            contents: None,
+            osc_type: None,
+            value: None,
+            range: None,
+            unit: None,
+            clipmode: None,
+            extended_type: None,
+            tags: None,
+            critical: None,
+            coercion: CoercionPolicy::Strict,
+            on_change: None,
+            host_info: None,
+        }
+    }
+
+    /// true if this node does not describe a parameter and has no sub nodes
+    fn is_empty_container(&self) -> bool {
+        self.osc_type.is_none() && self.contents.is_none()
+    }
+
+    /// split an OSC address into its parts, ignoring empty parts
+    fn split_address(path: &str) -> VecDeque<&str> {
+        path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+
+    /// join the path of a node with the key of one of its sub nodes
+    fn child_path(full_path: &str, key: &str) -> String {
+        if full_path.ends_with('/') {
+            full_path.to_string() + key
+        } else {
+            full_path.to_string() + "/" + key
+        }
+    }
+
+    /// walks down the tree along `addr` and creates new container nodes if necessary
+    fn get_or_create(&mut self, mut addr: VecDeque<&str>) -> &mut OSCNode {
         // if there is some address part left
         if let Some(key) = addr.pop_front() {
-            //Node does not have any content yet
-            if self.contents.is_none() {
-                self.contents = Some(BTreeMap::new())
-            }
-
-            //Node does not have the next address key jet
-            if !self.contents.as_ref().unwrap().contains_key(&key) {
-                let next_addr = self.full_path.to_string() + &key;
-                // new empty node for now
-                let value = OSCNode {
-                    description: "".to_string(),
-                    full_path: next_addr,
-                    access: Some(OSCAccess::NoAcces),
-                    contents: None,
-                    osc_type: None,
-                    value: None,
-                    range: None,
-                    unit: None,
-                    host_info: None,
-                };
-                self.contents
-                    .as_mut()
-                    .unwrap()
-                    .insert(key.to_string(), value);
+            let next_addr = Self::child_path(&self.full_path, key);
+            // run recursion on the next node in the tree,
+            // new empty node for now if the node does not have the next address key yet
+            self.contents
+                .get_or_insert_with(BTreeMap::new)
+                .entry(key.to_string())
+                .or_insert_with(|| OSCNode::container(next_addr))
+                .get_or_create(addr)
+        } else {
+            self
+        }
+    }
+
+    /// add a new Parameter to the root node.
+    /// A parameter that already exists at the same address is replaced.
+    pub fn add(&mut self, parameter: OscQueryParameter) -> Result<(), OscError> {
+        let addr = Self::split_address(&parameter.address);
+        let node = self.get_or_create(addr);
+
+        // insert into this address,
+        // replacing a parameter that was already there (sub nodes are kept)
+        node.description = parameter.description;
+        node.access = parameter.access;
+        node.tags = (!parameter.tags.is_empty()).then_some(parameter.tags);
+        node.critical = parameter.critical.then_some(true);
+        node.coercion = parameter.coercion;
+        node.on_change = parameter.on_change;
+
+        // work with all the optional values...
+        // the attribute arrays are only present if any argument has the attribute,
+        // arguments without it fill their place to keep the arrays aligned with TYPE
+        let arguments = parameter.arguments;
+        node.range = arguments
+            .iter()
+            .any(|argument| argument.range.is_some())
+            .then(Vec::new);
+        node.unit = arguments
+            .iter()
+            .any(|argument| argument.unit.is_some())
+            .then(Vec::new);
+        node.clipmode = arguments
+            .iter()
+            .any(|argument| argument.clipmode.is_some())
+            .then(Vec::new);
+        node.extended_type = arguments
+            .iter()
+            .any(|argument| argument.extended_type.is_some())
+            .then(Vec::new);
+        let mut values = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            if let Some(range) = node.range.as_mut() {
+                range.push(argument.range.unwrap_or(OscRange::Unbounded));
+            }
+            if let Some(unit) = node.unit.as_mut() {
+                unit.push(argument.unit);
+            }
+            if let Some(clipmode) = node.clipmode.as_mut() {
+                clipmode.push(argument.clipmode.unwrap_or_default());
             }
+            if let Some(extended_type) = node.extended_type.as_mut() {
+                extended_type.push(argument.extended_type);
+            }
+            values.push(argument.value);
+        }
+        node.osc_type = Some(values.clone());
+        node.value = Some(values);
+        Ok(())
+    }
 
-            // run recursion on the next node in the tree
-            self.contents
-                .as_mut()
-                .unwrap()
-                .get_mut(&key)
-                .unwrap()
-                .add_recursion(parameter, addr)?
+    /// replace an existing Parameter in the tree.
+    /// Fails with `OscError::BadAddress` if there is no parameter at the address yet.
+    pub fn replace(&mut self, parameter: OscQueryParameter) -> Result<(), OscError> {
+        match self.get(parameter.address.clone()) {
+            Ok(node) if node.osc_type.is_some() => self.add(parameter),
+            _ => Err(OscError::BadAddress(parameter.address)),
+        }
+    }
+
+    /// walks down the tree to remove the node at the end of `addr`,
+    /// container nodes that become empty on the way back up are removed as well
+    fn remove_recursion(
+        &mut self,
+        path: &str,
+        mut addr: VecDeque<&str>,
+    ) -> Result<OSCNode, OscError> {
+        let key = addr
+            .pop_front()
+            .ok_or(OscError::BadAddress(path.to_string()))?;
+        let contents = self
+            .contents
+            .as_mut()
+            .ok_or(OscError::BadAddress(path.to_string()))?;
+
+        let removed = if addr.is_empty() {
+            contents
+                .remove(key)
+                .ok_or(OscError::BadAddress(path.to_string()))?
         } else {
-            // base case insert into this address
-            self.description = parameter.description;
-            self.access = parameter.access;
-            self.full_path = parameter.address;
-
-            // work with all the optional values...
-            // TYPE
-            match &mut self.osc_type {
-                Some(v) => v.push(parameter.value.clone()),
-                None => self.osc_type = Some(vec![parameter.value.clone()]),
-            }
-            // UNIT
-            if let Some(unit) = parameter.unit {
-                match &mut self.unit {
-                    Some(v) => v.push(unit),
-                    None => self.unit = Some(vec![unit]),
-                }
+            let next = contents
+                .get_mut(key)
+                .ok_or(OscError::BadAddress(path.to_string()))?;
+            let removed = next.remove_recursion(path, addr)?;
+            if next.is_empty_container() {
+                contents.remove(key);
             }
-            // RANGE
-            if let Some(range) = parameter.range {
-                match &mut self.range {
-                    Some(v) => v.push(range),
-                    None => self.range = Some(vec![range]),
-                }
+            removed
+        };
+
+        if contents.is_empty() {
+            self.contents = None;
+        }
+        Ok(removed)
+    }
+
+    /// remove the node at `path` including all of its sub nodes and return it.
+    /// Container nodes that are left empty are pruned from the tree.
+    pub fn remove(&mut self, path: String) -> Result<OSCNode, OscError> {
+        let addr = Self::split_address(&path);
+        self.remove_recursion(&path, addr)
+    }
+
+    /// update the FULL_PATH of this node and all of its sub nodes
+    fn set_full_path(&mut self, full_path: String) {
+        self.full_path = full_path;
+        if let Some(contents) = self.contents.as_mut() {
+            for (key, node) in contents.iter_mut() {
+                node.set_full_path(Self::child_path(&self.full_path, key));
             }
-            // VALUE
-            match &mut self.value {
-                Some(v) => v.push(parameter.value),
-                None => self.value = Some(vec![parameter.value]),
+        }
+    }
+
+    /// move the node at `from` including all of its sub nodes to the new path `to`.
+    /// Fails with `OscError::BadAddress` if there is no node at `from`,
+    /// or if there already is a node at `to`.
+    pub fn rename(&mut self, from: String, to: String) -> Result<(), OscError> {
+        let to_addr = Self::split_address(&to);
+        let from_addr = Self::split_address(&from);
+        if from_addr.is_empty()
+            || to_addr.is_empty()
+            || to_addr.iter().take(from_addr.len()).eq(from_addr.iter())
+            || self.get(from.clone()).is_err()
+        {
+            return Err(OscError::BadAddress(from));
+        }
+        if self.get(to.clone()).is_ok() {
+            return Err(OscError::BadAddress(to));
+        }
+
+        let mut node = self.remove(from)?;
+        let target = self.get_or_create(to_addr);
+        node.set_full_path(target.full_path.clone());
+        *target = node;
+        Ok(())
+    }
+
+    /// find the shortest prefix of `path` that does not exist in the tree.
+    /// This is the topmost node that is created when adding something at `path`,
+    /// or the topmost node that was pruned after removing `path`.
+    pub(crate) fn first_missing(&self, path: &str) -> Option<String> {
+        let mut prefix = String::new();
+        for key in Self::split_address(path) {
+            prefix = prefix + "/" + key;
+            if self.get(prefix.clone()).is_err() {
+                return Some(prefix);
             }
         }
+        None
+    }
+
+    /// remove all sub nodes, the node itself (and its HOST_INFO) is kept
+    pub fn clear(&mut self) {
+        self.contents = None;
+    }
+
+    /// get a mutable subnode using a OSC path
+    fn get_mut(&mut self, path: &str) -> Result<&mut OSCNode, OscError> {
+        let mut node = self;
+        for key in Self::split_address(path) {
+            node = node
+                .contents
+                .as_mut()
+                .and_then(|contents| contents.get_mut(key))
+                .ok_or(OscError::BadAddress(path.to_string()))?;
+        }
+        Ok(node)
+    }
+
+    /// get the current VALUE of the parameter at `path`
+    pub fn value(&self, path: String) -> Result<&[OscType], OscError> {
+        self.get(path.clone())?
+            .value
+            .as_deref()
+            .ok_or(OscError::BadAddress(path))
+    }
+
+    /// set the VALUE of the parameter at `path`.
+    /// The values have to match the TYPE of the parameter, otherwise `OscError::BadArg` is returned.
+    /// Arguments with a CLIPMODE are clamped to their RANGE, values that are not in `VALS` are rejected.
+    /// ```
+    /// use oscq_rs::{OSCNode, OscQueryParameter};
+    /// use rosc::OscType;
+    /// let mut root = OSCNode::root(None);
+    /// root.add(OscQueryParameter::new("/test/param".to_string(), OscType::Int(42))).unwrap();
+    /// root.set_value("/test/param".to_string(), vec![OscType::Int(7)]).unwrap();
+    /// assert_eq!(root.value("/test/param".to_string()).unwrap(), &[OscType::Int(7)]);
+    /// assert!(root.set_value("/test/param".to_string(), vec![OscType::Float(7.0)]).is_err());
+    /// ```
+    pub fn set_value(&mut self, path: String, values: Vec<OscType>) -> Result<(), OscError> {
+        let (value, on_change) = self.store_value(&path, values)?;
+        if let Some(on_change) = on_change {
+            on_change.call(&value, &ChangeSource::Local);
+        }
         Ok(())
     }
 
-    /// add a new Parameter to the root node
-    pub fn add(&mut self, parameter: OscQueryParameter) -> Result<(), OscError> {
-        let mut addr: VecDeque<String> = parameter
-            .address
-            .split('/')
-            .map(|s| s.to_string())
-            .collect();
-        addr.pop_front();
-        self.add_recursion(parameter, addr)
+    /// set the `CoercionPolicy` used to convert the arguments of clients
+    /// to the TYPE of the parameter at `path`
+    /// ```
+    /// use oscq_rs::{CoercionPolicy, OSCNode, OscQueryParameter};
+    /// use rosc::OscType;
+    /// let mut root = OSCNode::root(None);
+    /// root.add(OscQueryParameter::new("/mute".to_string(), OscType::Bool(false))).unwrap();
+    /// root.set_coercion("/mute".to_string(), CoercionPolicy::LenientNumeric).unwrap();
+    /// ```
+    pub fn set_coercion(&mut self, path: String, coercion: CoercionPolicy) -> Result<(), OscError> {
+        let node = self.get_mut(&path)?;
+        if node.osc_type.is_none() {
+            return Err(OscError::BadAddress(path));
+        }
+        node.coercion = coercion;
+        Ok(())
+    }
+
+    /// check and store the VALUE of the parameter at `path` like `set_value`,
+    /// returns the stored (clipped) value and the `on_change` callback of the parameter
+    /// so it can be called once the tree is unlocked
+    pub(crate) fn store_value(
+        &mut self,
+        path: &str,
+        values: Vec<OscType>,
+    ) -> Result<(Vec<OscType>, Option<ChangeCallback>), OscError> {
+        let values = self.check_value(path, values)?;
+        let on_change = self.store_checked(path, values.clone())?;
+        Ok((values, on_change))
+    }
+
+    /// check a write of a client to the parameter at `path` like `check_value`,
+    /// the ACCESS of the parameter has to allow writing as well.
+    /// The arguments are converted to its TYPE according to its `CoercionPolicy` first.
+    pub(crate) fn check_write(
+        &self,
+        path: &str,
+        values: Vec<OscType>,
+    ) -> Result<Vec<OscType>, WriteRejection> {
+        let node = self
+            .get(path.to_string())
+            .map_err(|_| WriteRejection::NotFound(path.to_string()))?;
+        if let Some(access @ (OSCAccess::NoAcces | OSCAccess::Read)) = node.access {
+            return Err(WriteRejection::NotWritable {
+                path: path.to_string(),
+                access,
+            });
+        }
+        let values = node.coerce(values);
+        self.check_value(path, values)
+    }
+
+    /// check `values` against the TYPE of the parameter at `path` and clip them according to its CLIPMODE,
+    /// returns the values that would be stored
+    pub(crate) fn check_value(
+        &self,
+        path: &str,
+        values: Vec<OscType>,
+    ) -> Result<Vec<OscType>, WriteRejection> {
+        let not_found = || WriteRejection::NotFound(path.to_string());
+        let node = self.get(path.to_string()).map_err(|_| not_found())?;
+        let osc_type = node.osc_type.as_ref().ok_or_else(not_found)?;
+
+        if !osc_types_match(osc_type, &values) {
+            return Err(WriteRejection::TypeMismatch {
+                path: path.to_string(),
+                args: values,
+            });
+        }
+
+        // apply the CLIPMODE of every argument with its RANGE
+        match (&node.clipmode, &node.range) {
+            (Some(clipmode), Some(range)) => values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| match (clipmode.get(i), range.get(i)) {
+                    (Some(clipmode), Some(range)) => range.clip(value, *clipmode),
+                    _ => Ok(value),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|reason| WriteRejection::OutOfRange {
+                    path: path.to_string(),
+                    reason,
+                }),
+            _ => Ok(values),
+        }
+    }
+
+    /// convert `values` to the TYPE of this parameter according to its `CoercionPolicy`,
+    /// the values are returned unchanged if any of them can not be converted
+    fn coerce(&self, values: Vec<OscType>) -> Vec<OscType> {
+        match &self.osc_type {
+            Some(osc_type)
+                if self.coercion != CoercionPolicy::Strict && osc_type.len() == values.len() =>
+            {
+                osc_type
+                    .iter()
+                    .zip(values.iter().cloned())
+                    .map(|(osc_type, value)| self.coercion.coerce(osc_type, value))
+                    .collect::<Option<_>>()
+                    .unwrap_or(values)
+            }
+            _ => values,
+        }
+    }
+
+    /// the VALUE and the `on_change` callback of the parameter at `path`
+    pub(crate) fn value_and_callback(
+        &self,
+        path: &str,
+    ) -> Result<(Vec<OscType>, Option<ChangeCallback>), OscError> {
+        let node = self.get(path.to_string())?;
+        let value = node
+            .value
+            .clone()
+            .ok_or_else(|| OscError::BadAddress(path.to_string()))?;
+        Ok((value, node.on_change.clone()))
+    }
+
+    /// store values that passed `check_value`, returns the `on_change` callback of the parameter
+    pub(crate) fn store_checked(
+        &mut self,
+        path: &str,
+        values: Vec<OscType>,
+    ) -> Result<Option<ChangeCallback>, OscError> {
+        let node = self.get_mut(path)?;
+        node.value = Some(values);
+        Ok(node.on_change.clone())
     }
 
-    // get a subnode using a OSC path
+    /// get a subnode using a OSC path
     pub fn get(&self, path: String) -> Result<&OSCNode, OscError> {
         let path_s = path.clone();
         let mut addr: VecDeque<_> = path_s.split('/').collect();
@@ -390,48 +1529,267 @@ impl OSCNode {
             Ok(self)
         }
     }
-}
 
-/// convert a Vec of OscType to its OSC type string("f", "i", "fff" ...)
-fn osc_type_serialize<S: Serializer>(
-    addr: &Option<Vec<OscType>>,
-    serializer: S,
-) -> Result<S::Ok, S::Error> {
-    let mut s = String::new();
-    match addr {
-        Some(v) => {
-            for osc_type in v {
-                match osc_type {
-                    OscType::Int(_) => s += "i",
-                    OscType::Float(_) => s += "f",
-                    OscType::String(_) => s += "s",
-                    OscType::Blob(_) => s += "b",
-                    OscType::Time(_) => s += "t",
-                    OscType::Long(_) => s += "l",
-                    OscType::Double(_) => s += "d",
-                    OscType::Char(_) => s += "c",
-                    OscType::Color(_) => s += "r",
-                    OscType::Midi(_) => s += "m",
-                    OscType::Bool(_) => s += "T",
-                    OscType::Array(_) => todo!(),
-                    OscType::Nil => s += "N",
-                    OscType::Inf => s += "I",
+    /// find all nodes matching the OSC address `pattern`, relative to this node.
+    /// Supports the OSC 1.0 wildcards `*`, `?`, `[abc]`, `[a-z]`, `[!abc]`, `{foo,bar}`
+    /// and the OSC 1.1 `//` that matches any number of levels.
+    /// ```
+    /// use oscq_rs::{OSCNode, OscQueryParameter};
+    /// let mut root = OSCNode::root(None);
+    /// for path in ["/mixer/ch1/gain", "/mixer/ch2/gain", "/fx/reverb/mix", "/fx/delay/mix"] {
+    ///     root.add(OscQueryParameter::new(path.to_string(), rosc::OscType::Float(0.0))).unwrap();
+    /// }
+    /// assert_eq!(root.match_pattern("/mixer/ch*/gain").count(), 2);
+    /// assert_eq!(root.match_pattern("/fx/{reverb,delay}/mix").count(), 2);
+    /// assert_eq!(root.match_pattern("//mix").count(), 2);
+    /// ```
+    pub fn match_pattern(&self, pattern: &str) -> impl Iterator<Item = &OSCNode> {
+        let mut found = Vec::new();
+        if let Some(pattern) = pattern.strip_prefix('/') {
+            // "/a/" addresses the same node as "/a", but "/a//" all of its sub nodes
+            let pattern = match pattern.strip_suffix('/') {
+                Some(stripped) if !stripped.ends_with('/') => stripped,
+                _ => pattern,
+            };
+            let mut parts: Vec<Vec<char>> = match pattern {
+                "" => Vec::new(),
+                pattern => pattern
+                    .split('/')
+                    .map(|part| part.chars().collect())
+                    .collect(),
+            };
+            // "///" matches any number of levels just like "//"
+            parts.dedup_by(|a, b| a.is_empty() && b.is_empty());
+            self.match_parts(&parts, &mut found);
+        }
+        found.into_iter()
+    }
+
+    /// walk the tree with the split pattern, in the order of a depth first search.
+    /// Every node is visited at most once per part of the pattern,
+    /// so the time is bounded by the number of nodes times the number of parts.
+    fn match_parts<'a>(&'a self, parts: &[Vec<char>], found: &mut Vec<&'a OSCNode>) {
+        let mut visited = HashSet::new();
+        let mut matched = HashSet::new();
+        // (node, number of parts it has matched so far)
+        let mut pending = vec![(self, 0)];
+        while let Some((node, index)) = pending.pop() {
+            if !visited.insert((node as *const OSCNode, index)) {
+                // several "//" can reach the same node on different ways
+                continue;
+            }
+            let children = node.contents.iter().flat_map(|contents| contents.iter());
+            match parts.get(index) {
+                None => {
+                    if matched.insert(node as *const OSCNode) {
+                        found.push(node);
+                    }
+                }
+                // "//": the rest of the pattern matches here or at any level below
+                Some(part) if part.is_empty() => {
+                    pending.extend(children.rev().map(|(_, child)| (child, index)));
+                    pending.push((node, index + 1));
+                }
+                Some(part) => {
+                    let matching = children.rev().filter(|(key, _)| {
+                        let key: Vec<char> = key.chars().collect();
+                        osc_pattern_match(part, &key)
+                    });
+                    pending.extend(matching.map(|(_, child)| (child, index + 1)));
                 }
             }
-            serializer.serialize_str(s.as_str())
         }
-        None => serializer.serialize_none(),
     }
+
+    /// find all nodes below (and including) this node that carry the `tag` in their TAGS
+    /// ```
+    /// use oscq_rs::{OSCNode, OscQueryParameter};
+    /// let mut root = OSCNode::root(None);
+    /// root.add(OscQueryParameter::new("/light/dimmer".to_string(), rosc::OscType::Float(0.0))
+    ///     .with_tag("lighting")).unwrap();
+    /// assert_eq!(root.find_tagged("lighting").len(), 1);
+    /// ```
+    pub fn find_tagged(&self, tag: &str) -> Vec<&OSCNode> {
+        let mut found = Vec::new();
+        self.find_tagged_recursion(tag, &mut found);
+        found
+    }
+
+    fn find_tagged_recursion<'a>(&'a self, tag: &str, found: &mut Vec<&'a OSCNode>) {
+        if self.tags.iter().flatten().any(|t| t == tag) {
+            found.push(self);
+        }
+        for node in self.contents.iter().flat_map(|contents| contents.values()) {
+            node.find_tagged_recursion(tag, found);
+        }
+    }
+
+    /// the OSC address of this node
+    pub fn full_path(&self) -> &str {
+        &self.full_path
+    }
+
+    /// true if this node describes a parameter, i.e. it has a TYPE and a VALUE
+    pub fn is_parameter(&self) -> bool {
+        self.osc_type.is_some()
+    }
+
+    /// true if the VALUE of this node can be read by clients (ACCESS is read or read/write, or not given)
+    pub fn is_readable(&self) -> bool {
+        self.is_parameter()
+            && !matches!(
+                self.access,
+                Some(OSCAccess::NoAcces) | Some(OSCAccess::Write)
+            )
+    }
+
+    /// true if the VALUE of this node can be set by clients (ACCESS is write or read/write, or not given)
+    pub fn is_writable(&self) -> bool {
+        self.is_parameter()
+            && !matches!(
+                self.access,
+                Some(OSCAccess::NoAcces) | Some(OSCAccess::Read)
+            )
+    }
+
+    /// true if changes of this node need reliable delivery (CRITICAL)
+    pub fn is_critical(&self) -> bool {
+        self.critical.unwrap_or(false)
+    }
+
+    /// fill in the address of the OSC server in the HOST_INFO of this node (usually the root)
+    pub(crate) fn fill_host_info(&mut self, address: SocketAddr, transport: OscTransport) {
+        if let Some(host_info) = self.host_info.as_mut() {
+            host_info.fill_address(address, transport);
+        }
+    }
+
+    /// the address the OSC server of the HOST_INFO of this node should be bound to,
+    /// missing IP and port are replaced by the unspecified address and an ephemeral port
+    pub(crate) fn osc_address(&self) -> SocketAddr {
+        let host_info = self.host_info.as_deref();
+        let ip = host_info
+            .and_then(|host_info| host_info.osc_ip.as_ref())
+            .and_then(|ip| ip.parse().ok())
+            .unwrap_or(std::net::Ipv4Addr::UNSPECIFIED.into());
+        let port = host_info
+            .and_then(|host_info| host_info.osc_port)
+            .unwrap_or(0);
+        SocketAddr::new(ip, port)
+    }
+
+    /// the transport and framing the OSC server of the HOST_INFO of this node should use, UDP by default
+    pub(crate) fn osc_transport(&self) -> (OscTransport, OscFraming) {
+        match self.host_info.as_deref() {
+            Some(host_info) => (
+                host_info.osc_trans.unwrap_or(OscTransport::Udp),
+                host_info.framing,
+            ),
+            None => (OscTransport::Udp, OscFraming::default()),
+        }
+    }
+
+    /// the HOST_INFO of this node (usually the root) with the extensions it is serialized with
+    pub(crate) fn host_info(&self) -> Option<OscHostInfo> {
+        self.host_info.as_ref().map(|host_info| OscHostInfo {
+            extension: self.extensions(),
+            ..(**host_info).clone()
+        })
+    }
+
+    /// the sub node at `path` for serialization with the extensions enabled in the HOST_INFO of this node (the root),
+    /// like it is served by the OSCQuery service
+    /// ```
+    /// use oscq_rs::{OSCNode, OscHostInfo, OscQueryParameter};
+    /// let info = OscHostInfo::new("Test".to_string(), "127.0.0.1".to_string(), 9000).with_ext_value();
+    /// let mut root = OSCNode::root(Some(Box::new(info)));
+    /// root.add(OscQueryParameter::new("/gain".to_string(), rosc::OscType::Float(0.5))
+    ///     .with_description("Gain".to_string())).unwrap();
+    /// let json = serde_json::to_string(&root.view("/gain".to_string()).unwrap()).unwrap();
+    /// assert_eq!(json, r#"{"FULL_PATH":"/gain","TYPE":"f","VALUE":[0.5]}"#);
+    /// ```
+    pub fn view(&self, path: String) -> Result<OSCNodeView<'_>, OscError> {
+        Ok(OSCNodeView {
+            node: self.get(path)?,
+            extensions: self.extensions(),
+        })
+    }
+
+    /// the extensions this node (usually the root) is serialized with.
+    /// Without HOST_INFO all attributes are serialized.
+    pub(crate) fn extensions(&self) -> OscHostInfoExtension {
+        match &self.host_info {
+            None => OscHostInfoExtension::all(),
+            Some(host_info) if host_info.auto_extensions => {
+                let mut extensions = host_info.extension.clone();
+                self.used_extensions(&mut extensions);
+                extensions
+            }
+            Some(host_info) => host_info.extension.clone(),
+        }
+    }
+
+    /// enable the extensions of all attributes used by this node and its sub nodes
+    fn used_extensions(&self, ext: &mut OscHostInfoExtension) {
+        ext.description |= !self.description.is_empty();
+        ext.access |= self.osc_type.is_some() && self.access.is_some();
+        ext.value |= self.value.is_some();
+        ext.range |= self.range.is_some();
+        ext.unit |= self.unit.is_some();
+        ext.clipmode |= self.clipmode.is_some();
+        ext.extended_type |= self.extended_type.is_some();
+        ext.tags |= self.tags.is_some();
+        ext.critical |= self.critical.is_some();
+        for node in self.contents.iter().flat_map(|contents| contents.values()) {
+            node.used_extensions(ext);
+        }
+    }
+}
+
+/// check if `values` are of the types described by `osc_type` (the values of `osc_type` are ignored)
+fn osc_types_match(osc_type: &[OscType], values: &[OscType]) -> bool {
+    osc_type.len() == values.len()
+        && osc_type.iter().zip(values).all(|(t, v)| match (t, v) {
+            (OscType::Array(t), OscType::Array(v)) => osc_types_match(&t.content, &v.content),
+            (t, v) => std::mem::discriminant(t) == std::mem::discriminant(v),
+        })
 }
 
-/// Convert a OSC type string("i", "f", "fff"...) into a Vec of OscType
+/// convert a slice of OscType to its OSC type string("f", "i", "fff", "[ff]" ...)
+fn osc_type_tags(types: &[OscType]) -> String {
+    let mut s = String::new();
+    for osc_type in types {
+        match osc_type {
+            OscType::Int(_) => s += "i",
+            OscType::Float(_) => s += "f",
+            OscType::String(_) => s += "s",
+            OscType::Blob(_) => s += "b",
+            OscType::Time(_) => s += "t",
+            OscType::Long(_) => s += "l",
+            OscType::Double(_) => s += "d",
+            OscType::Char(_) => s += "c",
+            OscType::Color(_) => s += "r",
+            OscType::Midi(_) => s += "m",
+            OscType::Bool(true) => s += "T",
+            OscType::Bool(false) => s += "F",
+            OscType::Array(a) => s = s + "[" + &osc_type_tags(&a.content) + "]",
+            OscType::Nil => s += "N",
+            OscType::Inf => s += "I",
+        }
+    }
+    s
+}
+
+/// Convert a OSC type string("i", "f", "fff", "[ff]"...) into a Vec of OscType
 fn osc_type_deserialize<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<Option<Vec<OscType>>, D::Error> {
     let s = String::deserialize(deserializer)?;
     if !s.is_empty() {
-        let mut vec = Vec::new();
+        // the last entry collects the types of the innermost open array
+        let mut stack = vec![Vec::new()];
         for char in s.chars() {
+            let vec = stack.last_mut().unwrap();
             match char {
                 'i' => vec.push(OscType::Int(0i32)),
                 'f' => vec.push(OscType::Float(0f32)),
@@ -454,63 +1812,375 @@ fn osc_type_deserialize<'de, D: Deserializer<'de>>(
                     data2: 0,
                 })),
                 'T' => vec.push(OscType::Bool(true)),
+                'F' => vec.push(OscType::Bool(false)),
                 'N' => vec.push(OscType::Nil),
                 'I' => vec.push(OscType::Inf),
+                '[' => stack.push(Vec::new()),
+                ']' if stack.len() > 1 => {
+                    let content = stack.pop().unwrap();
+                    stack
+                        .last_mut()
+                        .unwrap()
+                        .push(OscType::Array(rosc::OscArray { content }));
+                }
+                ']' => return Err(serde::de::Error::custom("unmatched ']' in OSC Type")),
 
                 _ => {
                     return Err(serde::de::Error::unknown_variant(
                         char.to_string().as_str(),
                         &[
-                            "i", "f", "s", "b", "t", "l", "d", "c", "r", "m", "T", "N", "I",
+                            "i", "f", "s", "b", "t", "l", "d", "c", "r", "m", "T", "F", "N", "I",
+                            "[", "]",
                         ],
                     ))
                 }
             }
         }
-        Ok(Some(vec))
+        if stack.len() > 1 {
+            return Err(serde::de::Error::custom("unmatched '[' in OSC Type"));
+        }
+        Ok(stack.pop())
     } else {
         Err(serde::de::Error::custom("Invalid OSC Type"))
     }
 }
 
 use serde::ser::SerializeSeq;
-/// convert a Vec of OscType to its OSC type string("f", "i", "fff" ...)
-fn osc_value_serialize<S: Serializer>(
-    addr: &Option<Vec<OscType>>,
-    serializer: S,
-) -> Result<S::Ok, S::Error> {
-    match addr {
-        Some(values) => {
-            let mut seq = serializer.serialize_seq(Some(values.len()))?;
-            for val in values {
-                match val {
-                    OscType::Int(i) => seq.serialize_element(i)?,
-                    OscType::Float(f) => seq.serialize_element(f)?,
-                    OscType::String(g) => seq.serialize_element(g)?,
-                    OscType::Blob(b) => seq.serialize_element(b)?,
-                    OscType::Time(_t) => todo!(),
-                    OscType::Long(l) => seq.serialize_element(l)?,
-                    OscType::Double(d) => seq.serialize_element(d)?,
-                    OscType::Char(c) => seq.serialize_element(c)?,
-                    OscType::Color(_r) => todo!(),
-                    OscType::Midi(_m) => todo!(),
-                    OscType::Bool(b) => seq.serialize_element(b)?,
-                    OscType::Array(_a) => todo!(),
-                    OscType::Nil => todo!(),
-                    OscType::Inf => todo!(),
+/// JSON representation of a single OSC value in VALUE (and nested arrays)
+struct OscValueRef<'a>(&'a OscType);
+
+impl Serialize for OscValueRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            OscType::Int(i) => serializer.serialize_i32(*i),
+            OscType::Float(f) => serializer.serialize_f32(*f),
+            OscType::String(g) => serializer.serialize_str(g),
+            OscType::Blob(b) => b.serialize(serializer),
+            // NTP timestamp, seconds in the upper and fractions of a second in the lower 32 bits
+            OscType::Time(t) => {
+                serializer.serialize_u64(((t.seconds as u64) << 32) | t.fractional as u64)
+            }
+            OscType::Long(l) => serializer.serialize_i64(*l),
+            OscType::Double(d) => serializer.serialize_f64(*d),
+            OscType::Char(c) => serializer.serialize_char(*c),
+            // "#RRGGBBAA"
+            OscType::Color(r) => serializer.serialize_str(&format!(
+                "#{:02X}{:02X}{:02X}{:02X}",
+                r.red, r.green, r.blue, r.alpha
+            )),
+            // [port, status, data1, data2]
+            OscType::Midi(m) => [m.port, m.status, m.data1, m.data2].serialize(serializer),
+            OscType::Bool(b) => serializer.serialize_bool(*b),
+            OscType::Array(a) => {
+                let mut seq = serializer.serialize_seq(Some(a.content.len()))?;
+                for val in &a.content {
+                    seq.serialize_element(&OscValueRef(val))?;
                 }
+                seq.end()
             }
-            seq.end()
+            // Nil and Impulse (Infinitum) do not carry a value
+            OscType::Nil | OscType::Inf => serializer.serialize_unit(),
         }
-        None => serializer.serialize_none(),
     }
 }
 
-fn osc_value_deserialize<'de, D: Deserializer<'de>>(
-    _deserializer: D,
-) -> Result<Option<Vec<OscType>>, D::Error> {
-    // problem that the this Deserializer depends on the type value that is Deserializer separately
-    todo!()
+/// A node is serialized with the extensions of its own HOST_INFO (usually the root).
+/// A sub node does not know the HOST_INFO of its root and is serialized with all attributes,
+/// use `OSCNode::view` on the root to serialize a sub node with the extensions of the root.
+impl Serialize for OSCNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OSCNodeView {
+            node: self,
+            extensions: self.extensions(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// JSON representation of a node (and its sub nodes) with only the attributes of the enabled extensions,
+/// see `OSCNode::view`
+pub struct OSCNodeView<'a> {
+    pub(crate) node: &'a OSCNode,
+    pub(crate) extensions: OscHostInfoExtension,
+}
+
+impl Serialize for OSCNodeView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (node, ext) = (self.node, &self.extensions);
+        let mut map = serializer.serialize_map(None)?;
+        if ext.description {
+            map.serialize_entry("DESCRIPTION", &node.description)?;
+        }
+        map.serialize_entry("FULL_PATH", &node.full_path)?;
+        if let (true, Some(access)) = (ext.access, &node.access) {
+            map.serialize_entry("ACCESS", access)?;
+        }
+        if let Some(contents) = &node.contents {
+            let contents: BTreeMap<_, _> = contents
+                .iter()
+                .map(|(key, node)| {
+                    let view = OSCNodeView {
+                        node,
+                        extensions: ext.clone(),
+                    };
+                    (key, view)
+                })
+                .collect();
+            map.serialize_entry("CONTENTS", &contents)?;
+        }
+        if let Some(osc_type) = &node.osc_type {
+            // the tag of a bool (T or F) follows its current VALUE, unless the value is not disclosed
+            let tags = match &node.value {
+                Some(value) if node.is_readable() => osc_type_tags(value),
+                _ => osc_type_tags(osc_type),
+            };
+            map.serialize_entry("TYPE", &tags)?;
+        }
+        // the values of write only parameters are not disclosed
+        if let (true, true, Some(value)) = (ext.value, node.is_readable(), &node.value) {
+            let value: Vec<_> = value.iter().map(OscValueRef).collect();
+            map.serialize_entry("VALUE", &value)?;
+        }
+        if let (true, Some(range)) = (ext.range, &node.range) {
+            map.serialize_entry("RANGE", range)?;
+        }
+        if let (true, Some(unit)) = (ext.unit, &node.unit) {
+            map.serialize_entry("UNIT", unit)?;
+        }
+        if let (true, Some(clipmode)) = (ext.clipmode, &node.clipmode) {
+            map.serialize_entry("CLIPMODE", clipmode)?;
+        }
+        if let (true, Some(extended_type)) = (ext.extended_type, &node.extended_type) {
+            map.serialize_entry("EXTENDED_TYPE", extended_type)?;
+        }
+        if let (true, Some(tags)) = (ext.tags, &node.tags) {
+            map.serialize_entry("TAGS", tags)?;
+        }
+        if let (true, Some(critical)) = (ext.critical, &node.critical) {
+            map.serialize_entry("CRITICAL", critical)?;
+        }
+        if let Some(host_info) = &node.host_info {
+            // advertise the extensions the tree is actually serialized with
+            let host_info = OscHostInfo {
+                extension: ext.clone(),
+                ..(**host_info).clone()
+            };
+            map.serialize_entry("HOST_INFO", &host_info)?;
+        }
+        map.end()
+    }
+}
+
+/// Deserialization helper for `OSCNode`.
+/// The VALUE of a node can only be interpreted once its TYPE is known,
+/// so it is read as plain JSON first and converted in `TryFrom<OSCNodeRepr> for OSCNode`.
+#[derive(Deserialize)]
+struct OSCNodeRepr {
+    #[serde(rename = "DESCRIPTION", default)]
+    description: String,
+    #[serde(rename = "FULL_PATH")]
+    full_path: String,
+    #[serde(rename = "ACCESS", default)]
+    access: Option<OSCAccess>,
+    #[serde(rename = "CONTENTS", default)]
+    contents: Option<BTreeMap<String, OSCNode>>,
+    #[serde(rename = "TYPE", default, deserialize_with = "osc_type_deserialize")]
+    osc_type: Option<Vec<OscType>>,
+    #[serde(rename = "VALUE", default)]
+    value: Option<Vec<serde_json::Value>>,
+    #[serde(rename = "RANGE", default)]
+    range: Option<Vec<serde_json::Value>>,
+    #[serde(rename = "UNIT", default)]
+    unit: Option<Vec<Option<OSCUnit>>>,
+    #[serde(rename = "CLIPMODE", default)]
+    clipmode: Option<Vec<ClipMode>>,
+    #[serde(rename = "EXTENDED_TYPE", default)]
+    extended_type: Option<Vec<Option<OSCExtendedType>>>,
+    #[serde(rename = "TAGS", default)]
+    tags: Option<Vec<String>>,
+    #[serde(rename = "CRITICAL", default)]
+    critical: Option<bool>,
+    #[serde(rename = "HOST_INFO", default)]
+    host_info: Option<Box<OscHostInfo>>,
+}
+
+impl TryFrom<OSCNodeRepr> for OSCNode {
+    type Error = String;
+
+    fn try_from(repr: OSCNodeRepr) -> Result<Self, Self::Error> {
+        let value = match (repr.value, &repr.osc_type) {
+            (None, _) => None,
+            (Some(values), Some(osc_type)) => Some(osc_values_from_json(osc_type, values)?),
+            (Some(_), None) => return Err(format!("VALUE without TYPE at {}", repr.full_path)),
+        };
+        let range = match (repr.range, &repr.osc_type) {
+            (None, _) => None,
+            (Some(ranges), Some(osc_type)) => Some(osc_ranges_from_json(osc_type, ranges)?),
+            (Some(_), None) => return Err(format!("RANGE without TYPE at {}", repr.full_path)),
+        };
+
+        Ok(OSCNode {
+            description: repr.description,
+            full_path: repr.full_path,
+            access: repr.access,
+            contents: repr.contents,
+            osc_type: repr.osc_type,
+            value,
+            range,
+            unit: repr.unit,
+            clipmode: repr.clipmode,
+            extended_type: repr.extended_type,
+            tags: repr.tags,
+            critical: repr.critical,
+            host_info: repr.host_info,
+            coercion: CoercionPolicy::Strict,
+            on_change: None,
+        })
+    }
+}
+
+/// interpret the JSON `values` as the OSC types given by `osc_type`
+fn osc_values_from_json(
+    osc_type: &[OscType],
+    values: Vec<serde_json::Value>,
+) -> Result<Vec<OscType>, String> {
+    if osc_type.len() != values.len() {
+        return Err(format!(
+            "{} VALUE entries for {} TYPE entries",
+            values.len(),
+            osc_type.len()
+        ));
+    }
+    osc_type
+        .iter()
+        .zip(values)
+        .map(|(osc_type, value)| osc_value_from_json(osc_type, value))
+        .collect()
+}
+
+/// interpret the JSON `ranges` as RANGE entries for values of the types given by `osc_type`
+fn osc_ranges_from_json(
+    osc_type: &[OscType],
+    ranges: Vec<serde_json::Value>,
+) -> Result<Vec<OscRange>, String> {
+    if ranges.len() > osc_type.len() {
+        return Err(format!(
+            "{} RANGE entries for {} TYPE entries",
+            ranges.len(),
+            osc_type.len()
+        ));
+    }
+    osc_type
+        .iter()
+        .zip(ranges)
+        .map(|(osc_type, range)| osc_range_from_json(osc_type, range))
+        .collect()
+}
+
+/// interpret a single JSON `range` as RANGE of a value of the type of `osc_type`,
+/// the bounds are converted to that type
+fn osc_range_from_json(osc_type: &OscType, range: serde_json::Value) -> Result<OscRange, String> {
+    match (osc_type, range) {
+        (_, serde_json::Value::Null) => Ok(OscRange::Unbounded),
+        (OscType::Array(a), serde_json::Value::Array(ranges)) => {
+            Ok(OscRange::Array(osc_ranges_from_json(&a.content, ranges)?))
+        }
+        (osc_type, range) => {
+            let mut bounds: BTreeMap<OscRangeBounds, serde_json::Value> =
+                serde_json::from_value(range).map_err(|err| format!("invalid RANGE: {}", err))?;
+            let mut bound = |key| {
+                bounds
+                    .remove(&key)
+                    .map(|value| osc_value_from_json(osc_type, value))
+                    .transpose()
+            };
+            let min = bound(OscRangeBounds::Min)?;
+            let max = bound(OscRangeBounds::Max)?;
+            let vals = match bounds.remove(&OscRangeBounds::Discrete) {
+                Some(serde_json::Value::Array(vals)) => Some(
+                    vals.into_iter()
+                        .map(|value| osc_value_from_json(osc_type, value))
+                        .collect::<Result<_, _>>()?,
+                ),
+                Some(vals) => return Err(format!("invalid VALS {}", vals)),
+                None => None,
+            };
+            Ok(OscRange::Bounds(OscBounds { min, max, vals }))
+        }
+    }
+}
+
+/// interpret a single JSON `value` as the OSC type of `osc_type`
+fn osc_value_from_json(osc_type: &OscType, value: serde_json::Value) -> Result<OscType, String> {
+    use serde_json::Value;
+
+    let invalid = |value: &Value| format!("invalid VALUE {} for TYPE {:?}", value, osc_type);
+    let byte = |value: &Value| {
+        value
+            .as_u64()
+            .and_then(|b| u8::try_from(b).ok())
+            .ok_or_else(|| invalid(value))
+    };
+
+    match (osc_type, &value) {
+        // only integers that fit the TYPE, floats are not rounded
+        (OscType::Int(_), Value::Number(n)) => n
+            .as_i64()
+            .and_then(|i| i32::try_from(i).ok())
+            .map(OscType::Int),
+        (OscType::Long(_), Value::Number(n)) => n.as_i64().map(OscType::Long),
+        (OscType::Float(_), Value::Number(n)) => n.as_f64().map(|f| OscType::Float(f as f32)),
+        (OscType::Double(_), Value::Number(n)) => n.as_f64().map(OscType::Double),
+        (OscType::String(_), Value::String(s)) => Some(OscType::String(s.clone())),
+        (OscType::Char(_), Value::String(s)) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(OscType::Char(c)),
+                _ => None,
+            }
+        }
+        (OscType::Bool(_), Value::Bool(b)) => Some(OscType::Bool(*b)),
+        (OscType::Blob(_), Value::Array(bytes)) => Some(OscType::Blob(
+            bytes.iter().map(byte).collect::<Result<_, _>>()?,
+        )),
+        // NTP timestamp, seconds in the upper and fractions of a second in the lower 32 bits
+        (OscType::Time(_), Value::Number(n)) => n.as_u64().map(|t| {
+            OscType::Time(rosc::OscTime {
+                seconds: (t >> 32) as u32,
+                fractional: t as u32,
+            })
+        }),
+        // "#RRGGBBAA"
+        (OscType::Color(_), Value::String(s)) => {
+            let hex = s.strip_prefix('#').unwrap_or(s);
+            let rgba = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 8);
+            rgba.map(|rgba| {
+                let [red, green, blue, alpha] = rgba.to_be_bytes();
+                OscType::Color(rosc::OscColor {
+                    red,
+                    green,
+                    blue,
+                    alpha,
+                })
+            })
+        }
+        // [port, status, data1, data2]
+        (OscType::Midi(_), Value::Array(bytes)) if bytes.len() == 4 => {
+            Some(OscType::Midi(rosc::OscMidiMessage {
+                port: byte(&bytes[0])?,
+                status: byte(&bytes[1])?,
+                data1: byte(&bytes[2])?,
+                data2: byte(&bytes[3])?,
+            }))
+        }
+        (OscType::Array(osc_type), Value::Array(values)) => Some(OscType::Array(rosc::OscArray {
+            content: osc_values_from_json(&osc_type.content, values.clone())?,
+        })),
+        (OscType::Nil, Value::Null) => Some(OscType::Nil),
+        (OscType::Inf, Value::Null) => Some(OscType::Inf),
+        _ => None,
+    }
+    .ok_or_else(|| invalid(&value))
 }
 
 ///-----------------------------------
@@ -518,10 +2188,13 @@ fn osc_value_deserialize<'de, D: Deserializer<'de>>(
 ///-----------------------------------
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn serialize_osc_node() {
-    let mut range = BTreeMap::new();
-    range.insert(OscRangeBounds::Min, 100.0f32);
-    range.insert(OscRangeBounds::Max, 200.0f32);
+    let range = OscBounds {
+        min: Some(OscType::Float(100.0)),
+        max: Some(OscType::Float(200.0)),
+        vals: None,
+    };
 
     let node = OSCNode {
         description: "A test node".to_string(),
@@ -538,9 +2211,15 @@ fn serialize_osc_node() {
                     contents: None,
                     osc_type: Some(vec![OscType::Int(0)]),
                     value: Some(vec![OscType::Int(123)]),
-                    range: Some(vec![range]),
+                    range: Some(vec![OscRange::Bounds(range)]),
                     unit: None,
+                    clipmode: None,
+                    extended_type: None,
+                    tags: None,
+                    critical: None,
                     host_info: None,
+                    coercion: CoercionPolicy::Strict,
+                    on_change: None,
                 },
             );
             contents
@@ -549,10 +2228,16 @@ fn serialize_osc_node() {
         value: Some(vec![OscType::Float(3.1234), OscType::Float(2.7182)]),
         range: None,
         unit: Some(vec![
-            OSCUnit::Distance(crate::OSCDistance::Meter),
-            OSCUnit::Speed(crate::OSCSpeed::KilometersPerHour),
+            Some(OSCUnit::Distance(crate::OSCDistance::Meter)),
+            Some(OSCUnit::Speed(crate::OSCSpeed::KilometersPerHour)),
         ]),
+        clipmode: None,
+        extended_type: None,
+        tags: None,
+        critical: None,
         host_info: None,
+        coercion: CoercionPolicy::Strict,
+        on_change: None,
     };
 
     let serialized = serde_json::to_string(&node).unwrap();
@@ -581,10 +2266,12 @@ fn add_parameters() {
 
     let info = OscHostInfo {
         name: "OSCQuery Test".to_string(),
-        osc_ip: "127.0.0.1".to_string(),
-        osc_port: 6666,
+        osc_ip: Some("127.0.0.1".to_string()),
+        osc_port: Some(6666),
         extension: ext,
-        osc_trans: "UDP".to_string(),
+        osc_trans: Some(OscTransport::Udp),
+        framing: OscFraming::default(),
+        auto_extensions: false,
     };
 
     let mut root = OSCNode::root(Some(Box::new(info)));
@@ -618,3 +2305,952 @@ fn add_parameters() {
 
     println!("{}\n\n", serialized);
 }
+
+#[test]
+fn replace_parameters() {
+    let mut root = OSCNode::root(None);
+
+    root.add(
+        OscQueryParameter::new("/group/test".to_string(), OscType::Float(1f32))
+            .with_min_max(0f32, 10f32),
+    )
+    .unwrap();
+    root.add(OscQueryParameter::new(
+        "/group/test/subtest".to_string(),
+        OscType::Int(1),
+    ))
+    .unwrap();
+
+    // adding again at the same address replaces TYPE/VALUE/RANGE instead of appending
+    root.add(OscQueryParameter::new(
+        "/group/test".to_string(),
+        OscType::Int(5),
+    ))
+    .unwrap();
+    let node = root.get("/group/test".to_string()).unwrap();
+    assert_eq!(node.osc_type.as_ref().unwrap().len(), 1);
+    assert_eq!(node.value, Some(vec![OscType::Int(5)]));
+    assert!(node.range.is_none());
+    assert!(root.get("/group/test/subtest".to_string()).is_ok());
+
+    root.replace(OscQueryParameter::new(
+        "/group/test".to_string(),
+        OscType::Int(6),
+    ))
+    .unwrap();
+    assert_eq!(
+        root.get("/group/test".to_string()).unwrap().value,
+        Some(vec![OscType::Int(6)])
+    );
+
+    // replace does not create new nodes
+    assert!(root
+        .replace(OscQueryParameter::new(
+            "/group/other".to_string(),
+            OscType::Int(6),
+        ))
+        .is_err());
+    assert!(root
+        .replace(OscQueryParameter::new(
+            "/group".to_string(),
+            OscType::Int(6)
+        ))
+        .is_err());
+}
+
+#[test]
+fn remove_parameters() {
+    let mut root = OSCNode::root(None);
+    root.add(OscQueryParameter::new(
+        "/plugin/a/gain".to_string(),
+        OscType::Float(1f32),
+    ))
+    .unwrap();
+    root.add(OscQueryParameter::new(
+        "/plugin/b/gain".to_string(),
+        OscType::Float(1f32),
+    ))
+    .unwrap();
+    root.add(OscQueryParameter::new(
+        "/master".to_string(),
+        OscType::Float(1f32),
+    ))
+    .unwrap();
+    assert_eq!(
+        root.get("/plugin/a/gain".to_string()).unwrap().full_path,
+        "/plugin/a/gain"
+    );
+    assert_eq!(
+        root.get("/plugin/a".to_string()).unwrap().full_path,
+        "/plugin/a"
+    );
+
+    let removed = root.remove("/plugin/a/gain".to_string()).unwrap();
+    assert_eq!(removed.full_path, "/plugin/a/gain");
+    // the now empty container "/plugin/a" is pruned, "/plugin" still holds "b"
+    assert!(root.get("/plugin/a".to_string()).is_err());
+    assert!(root.get("/plugin/b/gain".to_string()).is_ok());
+
+    // removing a whole subtree
+    let removed = root.remove("/plugin".to_string()).unwrap();
+    assert!(removed.get("/b/gain".to_string()).is_ok());
+    assert!(root.get("/plugin".to_string()).is_err());
+    assert!(root.remove("/plugin".to_string()).is_err());
+
+    root.clear();
+    assert!(root.contents.is_none());
+    assert!(root.get("/master".to_string()).is_err());
+}
+
+#[test]
+fn set_parameter_values() {
+    let mut root = OSCNode::root(None);
+    root.add(OscQueryParameter::new(
+        "/show/cue".to_string(),
+        OscType::Int(0),
+    ))
+    .unwrap();
+    root.add(OscQueryParameter::new(
+        "/show/name".to_string(),
+        OscType::String("".to_string()),
+    ))
+    .unwrap();
+
+    root.set_value("/show/cue".to_string(), vec![OscType::Int(12)])
+        .unwrap();
+    root.set_value(
+        "/show/name".to_string(),
+        vec![OscType::String("Act 2".to_string())],
+    )
+    .unwrap();
+    assert_eq!(
+        root.value("/show/cue".to_string()).unwrap(),
+        &[OscType::Int(12)]
+    );
+
+    let serialized = serde_json::to_string(root.get("/show/name".to_string()).unwrap()).unwrap();
+    assert!(serialized.contains(r#""TYPE":"s","VALUE":["Act 2"]"#));
+
+    // wrong type, wrong number of arguments, no parameter at the path
+    assert!(root
+        .set_value("/show/cue".to_string(), vec![OscType::Float(1.0)])
+        .is_err());
+    assert!(root
+        .set_value(
+            "/show/cue".to_string(),
+            vec![OscType::Int(1), OscType::Int(2)]
+        )
+        .is_err());
+    assert!(root
+        .set_value("/show".to_string(), vec![OscType::Int(1)])
+        .is_err());
+    assert!(root.value("/show/missing".to_string()).is_err());
+    assert_eq!(
+        root.value("/show/cue".to_string()).unwrap(),
+        &[OscType::Int(12)]
+    );
+}
+
+#[test]
+fn rename_nodes() {
+    let mut root = OSCNode::root(None);
+    root.add(OscQueryParameter::new(
+        "/fx/reverb/mix".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    root.add(OscQueryParameter::new(
+        "/fx/delay/mix".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+
+    root.rename("/fx/reverb".to_string(), "/bus/a/hall".to_string())
+        .unwrap();
+    assert!(root.get("/fx/reverb".to_string()).is_err());
+    let node = root.get("/bus/a/hall/mix".to_string()).unwrap();
+    assert_eq!(node.full_path, "/bus/a/hall/mix");
+    assert!(node.osc_type.is_some());
+
+    // the target exists, the source does not exist, the target is inside the source
+    assert!(root
+        .rename("/fx/delay".to_string(), "/bus/a/hall".to_string())
+        .is_err());
+    assert!(root
+        .rename("/fx/reverb".to_string(), "/fx/hall".to_string())
+        .is_err());
+    assert!(root
+        .rename("/fx".to_string(), "/fx/inner".to_string())
+        .is_err());
+    assert!(root.get("/fx/delay/mix".to_string()).is_ok());
+
+    assert_eq!(root.first_missing("/fx/delay/mix"), None);
+    assert_eq!(
+        root.first_missing("/fx/chorus/mix"),
+        Some("/fx/chorus".to_string())
+    );
+}
+
+#[test]
+fn deserialize_osc_node() {
+    let json = r##"{
+        "FULL_PATH": "/",
+        "CONTENTS": {
+            "foo": {
+                "DESCRIPTION": "all the types",
+                "FULL_PATH": "/foo",
+                "ACCESS": 3,
+                "TYPE": "ifsbldcTFNIrm",
+                "VALUE": [1, 2.5, "bar", [1, 2, 3], 4, 5.5, "x", true, false, null, null, "#FF8000C0", [0, 144, 60, 127]]
+            },
+            "bar": {
+                "FULL_PATH": "/bar",
+                "TYPE": "fi",
+                "VALUE": [1, 2],
+                "RANGE": [{"MIN": 0.0, "MAX": 10.0}],
+                "UNIT": ["distance.m"]
+            }
+        }
+    }"##;
+
+    let root: OSCNode = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        root.value("/foo".to_string()).unwrap(),
+        &[
+            OscType::Int(1),
+            OscType::Float(2.5),
+            OscType::String("bar".to_string()),
+            OscType::Blob(vec![1, 2, 3]),
+            OscType::Long(4),
+            OscType::Double(5.5),
+            OscType::Char('x'),
+            OscType::Bool(true),
+            OscType::Bool(false),
+            OscType::Nil,
+            OscType::Inf,
+            OscType::Color(rosc::OscColor {
+                red: 255,
+                green: 128,
+                blue: 0,
+                alpha: 192
+            }),
+            OscType::Midi(rosc::OscMidiMessage {
+                port: 0,
+                status: 144,
+                data1: 60,
+                data2: 127
+            }),
+        ]
+    );
+    // ints and floats are read according to TYPE, not to the JSON number format
+    assert_eq!(
+        root.value("/bar".to_string()).unwrap(),
+        &[OscType::Float(1.0), OscType::Int(2)]
+    );
+    assert_eq!(root.get("/bar".to_string()).unwrap().description, "");
+}
+
+#[test]
+fn deserialize_osc_node_round_trip() {
+    let mut root = OSCNode::root(None);
+    root.add(
+        OscQueryParameter::new("/group/test".to_string(), OscType::Float(1.5))
+            .with_description("My First Description".to_string())
+            .with_min_max(0f32, 10f32)
+            .with_access(OSCAccess::ReadWrite)
+            .with_unit(OSCUnit::Distance(crate::OSCDistance::Centimeter)),
+    )
+    .unwrap();
+    root.add(OscQueryParameter::new(
+        "/group/name".to_string(),
+        OscType::String("abc".to_string()),
+    ))
+    .unwrap();
+
+    let serialized = serde_json::to_string(&root).unwrap();
+    let deserialized: OSCNode = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+}
+
+#[test]
+fn deserialize_invalid_values() {
+    for json in [
+        r#"{"FULL_PATH":"/a","TYPE":"i","VALUE":["one"]}"#,
+        r#"{"FULL_PATH":"/a","TYPE":"i","VALUE":[1, 2]}"#,
+        r#"{"FULL_PATH":"/a","TYPE":"i","VALUE":[1.5]}"#,
+        r#"{"FULL_PATH":"/a","TYPE":"i","VALUE":[2147483648]}"#,
+        r#"{"FULL_PATH":"/a","TYPE":"h","VALUE":[2.0]}"#,
+        r#"{"FULL_PATH":"/a","TYPE":"h","VALUE":[9223372036854775808]}"#,
+        r#"{"FULL_PATH":"/a","TYPE":"c","VALUE":["ab"]}"#,
+        r##"{"FULL_PATH":"/a","TYPE":"r","VALUE":["#FF00"]}"##,
+        r#"{"FULL_PATH":"/a","TYPE":"b","VALUE":[[256]]}"#,
+        r#"{"FULL_PATH":"/a","VALUE":[1]}"#,
+    ] {
+        assert!(serde_json::from_str::<OSCNode>(json).is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn serialize_all_values() {
+    let to_json = |value: OscType| serde_json::to_string(&OscValueRef(&value)).unwrap();
+
+    assert_eq!(to_json(OscType::Int(-3)), "-3");
+    assert_eq!(to_json(OscType::Float(0.5)), "0.5");
+    assert_eq!(to_json(OscType::String("a".to_string())), r#""a""#);
+    assert_eq!(to_json(OscType::Blob(vec![1, 2])), "[1,2]");
+    assert_eq!(
+        to_json(OscType::Time(rosc::OscTime {
+            seconds: 1,
+            fractional: 2
+        })),
+        "4294967298"
+    );
+    assert_eq!(to_json(OscType::Long(1 << 40)), "1099511627776");
+    assert_eq!(to_json(OscType::Double(0.25)), "0.25");
+    assert_eq!(to_json(OscType::Char('c')), r#""c""#);
+    assert_eq!(
+        to_json(OscType::Color(rosc::OscColor {
+            red: 255,
+            green: 1,
+            blue: 16,
+            alpha: 128
+        })),
+        r##""#FF011080""##
+    );
+    assert_eq!(
+        to_json(OscType::Midi(rosc::OscMidiMessage {
+            port: 1,
+            status: 144,
+            data1: 60,
+            data2: 100
+        })),
+        "[1,144,60,100]"
+    );
+    assert_eq!(to_json(OscType::Bool(false)), "false");
+    assert_eq!(
+        to_json(OscType::Array(rosc::OscArray {
+            content: vec![
+                OscType::Int(1),
+                OscType::Array(rosc::OscArray {
+                    content: vec![OscType::Nil]
+                })
+            ]
+        })),
+        "[1,[null]]"
+    );
+    assert_eq!(to_json(OscType::Nil), "null");
+    assert_eq!(to_json(OscType::Inf), "null");
+}
+
+#[test]
+fn round_trip_all_values() {
+    let values = vec![
+        OscType::Int(i32::MIN),
+        OscType::Float(-1.25),
+        OscType::String("text".to_string()),
+        OscType::Blob(vec![0, 127, 255]),
+        OscType::Time(rosc::OscTime {
+            seconds: 3_900_000_000,
+            fractional: 123_456,
+        }),
+        OscType::Long(i64::MAX),
+        OscType::Double(1e-10),
+        OscType::Char('ö'),
+        OscType::Color(rosc::OscColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+            alpha: 4,
+        }),
+        OscType::Midi(rosc::OscMidiMessage {
+            port: 0,
+            status: 176,
+            data1: 7,
+            data2: 127,
+        }),
+        OscType::Bool(true),
+        OscType::Bool(false),
+        OscType::Nil,
+        OscType::Inf,
+    ];
+
+    let mut root = OSCNode::root(None);
+    for (i, value) in values.iter().enumerate() {
+        root.add(OscQueryParameter::new(
+            format!("/values/{}", i),
+            value.clone(),
+        ))
+        .unwrap();
+    }
+
+    let serialized = serde_json::to_string(&root).unwrap();
+    let deserialized: OSCNode = serde_json::from_str(&serialized).unwrap();
+    for (i, value) in values.into_iter().enumerate() {
+        assert_eq!(
+            deserialized.value(format!("/values/{}", i)).unwrap(),
+            &[value]
+        );
+    }
+}
+
+#[test]
+fn bool_type_tags() {
+    for value in [true, false] {
+        let mut root = OSCNode::root(None);
+        root.add(OscQueryParameter::new(
+            "/mute".to_string(),
+            OscType::Bool(value),
+        ))
+        .unwrap();
+
+        let serialized = serde_json::to_string(&root).unwrap();
+        let tag = if value {
+            r#""TYPE":"T""#
+        } else {
+            r#""TYPE":"F""#
+        };
+        assert!(serialized.contains(tag), "{}", serialized);
+
+        let deserialized: OSCNode = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(
+            deserialized.value("/mute".to_string()).unwrap(),
+            &[OscType::Bool(value)]
+        );
+        assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+
+        // the TYPE alone is kept as well
+        let json = format!(r#"{{"FULL_PATH":"/",{}}}"#, tag);
+        let deserialized: OSCNode = serde_json::from_str(&json).unwrap();
+        assert!(serde_json::to_string(&deserialized).unwrap().contains(tag));
+    }
+
+    // the TYPE follows the VALUE
+    let mut root = OSCNode::root(None);
+    root.add(OscQueryParameter::new(
+        "/mute".to_string(),
+        OscType::Bool(false),
+    ))
+    .unwrap();
+    let type_of = |root: &OSCNode| {
+        let json = serde_json::to_value(root.get("/mute".to_string()).unwrap()).unwrap();
+        json["TYPE"].as_str().unwrap().to_string()
+    };
+    assert_eq!(type_of(&root), "F");
+    root.set_value("/mute".to_string(), vec![OscType::Bool(true)])
+        .unwrap();
+    assert_eq!(type_of(&root), "T");
+    root.set_value("/mute".to_string(), vec![OscType::Bool(false)])
+        .unwrap();
+    assert_eq!(type_of(&root), "F");
+}
+
+#[test]
+fn array_types() {
+    let mut root = OSCNode::root(None);
+    root.add(
+        OscQueryParameter::array("/mixer/eq".to_string(), vec![OscType::Float(0.0); 3])
+            .with_min_max(-12.0, 12.0),
+    )
+    .unwrap();
+    root.add(OscQueryParameter::new(
+        "/mixer/matrix".to_string(),
+        OscType::Array(rosc::OscArray {
+            content: vec![
+                OscType::Int(1),
+                OscType::Array(rosc::OscArray {
+                    content: vec![OscType::Int(2), OscType::String("x".to_string())],
+                }),
+            ],
+        }),
+    ))
+    .unwrap();
+
+    let eq = serde_json::to_string(root.get("/mixer/eq".to_string()).unwrap()).unwrap();
+    assert!(eq.contains(r#""TYPE":"[fff]","VALUE":[[0.0,0.0,0.0]],"RANGE":[[{"MIN":-12.0,"MAX":12.0},{"MIN":-12.0,"MAX":12.0},{"MIN":-12.0,"MAX":12.0}]]"#));
+    let matrix = serde_json::to_string(root.get("/mixer/matrix".to_string()).unwrap()).unwrap();
+    assert!(matrix.contains(r#""TYPE":"[i[is]]","VALUE":[[1,[2,"x"]]]"#));
+
+    // values are checked against the element types of the array
+    let eq_value = |values: Vec<OscType>| vec![OscType::Array(rosc::OscArray { content: values })];
+    root.set_value(
+        "/mixer/eq".to_string(),
+        eq_value(vec![OscType::Float(3.0); 3]),
+    )
+    .unwrap();
+    assert!(root
+        .set_value(
+            "/mixer/eq".to_string(),
+            eq_value(vec![OscType::Float(3.0); 2])
+        )
+        .is_err());
+    assert!(root
+        .set_value("/mixer/eq".to_string(), eq_value(vec![OscType::Int(3); 3]))
+        .is_err());
+
+    let serialized = serde_json::to_string(&root).unwrap();
+    let deserialized: OSCNode = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+    assert_eq!(
+        deserialized.value("/mixer/eq".to_string()).unwrap(),
+        eq_value(vec![OscType::Float(3.0); 3]).as_slice()
+    );
+}
+
+#[test]
+fn invalid_array_types() {
+    for osc_type in ["[f", "f]", "[[f]", "[f]]"] {
+        let json = format!(r#"{{"FULL_PATH":"/a","TYPE":"{}"}}"#, osc_type);
+        assert!(
+            serde_json::from_str::<OSCNode>(&json).is_err(),
+            "{}",
+            osc_type
+        );
+    }
+    let root: OSCNode = serde_json::from_str(r#"{"FULL_PATH":"/a","TYPE":"f[]"}"#).unwrap();
+    assert_eq!(
+        root.osc_type,
+        Some(vec![
+            OscType::Float(0.0),
+            OscType::Array(rosc::OscArray { content: vec![] })
+        ])
+    );
+}
+
+#[test]
+fn typed_ranges() {
+    let mut root = OSCNode::root(None);
+    root.add(
+        OscQueryParameter::new("/layer/blend".to_string(), "normal".into())
+            .with_vals(["normal", "add", "multiply"])
+            .unwrap(),
+    )
+    .unwrap();
+    root.add(
+        OscQueryParameter::new("/layer/index".to_string(), OscType::Int(1))
+            .with_min_max(0.0, 7.0)
+            .with_vals([1, 2, 4])
+            .unwrap(),
+    )
+    .unwrap();
+    root.add(
+        OscQueryParameter::new("/clock/ticks".to_string(), OscType::Long(0))
+            .with_min(0i64)
+            .unwrap(),
+    )
+    .unwrap();
+    root.add(
+        OscQueryParameter::new("/clock/rate".to_string(), OscType::Double(1.0))
+            .with_min_max(0.5, 2.0),
+    )
+    .unwrap();
+
+    let json = |path: &str| serde_json::to_string(root.get(path.to_string()).unwrap()).unwrap();
+    assert!(json("/layer/blend").contains(r#""RANGE":[{"VALS":["normal","add","multiply"]}]"#));
+    assert!(json("/layer/index").contains(r#""RANGE":[{"MIN":0,"MAX":7,"VALS":[1,2,4]}]"#));
+    assert!(json("/clock/ticks").contains(r#""RANGE":[{"MIN":0}]"#));
+    assert!(json("/clock/rate").contains(r#""RANGE":[{"MIN":0.5,"MAX":2.0}]"#));
+
+    // bounds are read back in the type of the value
+    let serialized = serde_json::to_string(&root).unwrap();
+    let deserialized: OSCNode = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+    assert_eq!(
+        deserialized.get("/layer/index".to_string()).unwrap().range,
+        Some(vec![OscRange::Bounds(OscBounds {
+            min: Some(OscType::Int(0)),
+            max: Some(OscType::Int(7)),
+            vals: Some(vec![OscType::Int(1), OscType::Int(2), OscType::Int(4)]),
+        })])
+    );
+
+    let json = r#"{"FULL_PATH":"/a","TYPE":"f","RANGE":[{"MIN":"low"}]}"#;
+    assert!(serde_json::from_str::<OSCNode>(json).is_err());
+}
+
+#[test]
+fn multi_argument_parameters() {
+    let mut root = OSCNode::root(None);
+    root.add(
+        OscQueryParameter::new("/color".to_string(), OscType::Float(1.0))
+            .with_min_max(0.0, 1.0)
+            .with_clipmode(ClipMode::Both)
+            .with_argument(OscQueryArgument::new(OscType::Float(0.5)).with_min_max(0.0, 1.0))
+            .with_argument(OscQueryArgument::new(OscType::Int(0)))
+            .with_argument(
+                OscQueryArgument::new(OscType::Float(0.0))
+                    .with_unit(OSCUnit::Distance(crate::OSCDistance::Meter)),
+            ),
+    )
+    .unwrap();
+
+    // the attributes of the arguments are aligned with TYPE, missing ones are filled in
+    let json = serde_json::to_string(root.get("/color".to_string()).unwrap()).unwrap();
+    assert_eq!(
+        json,
+        r#"{"DESCRIPTION":"","FULL_PATH":"/color","TYPE":"ffif","VALUE":[1.0,0.5,0,0.0],"RANGE":[{"MIN":0.0,"MAX":1.0},{"MIN":0.0,"MAX":1.0},null,null],"UNIT":[null,null,null,"distance.m"],"CLIPMODE":["both","none","none","none"]}"#
+    );
+
+    let deserialized: OSCNode = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+    assert_eq!(deserialized.range.as_ref().unwrap()[2], OscRange::Unbounded);
+
+    root.set_value(
+        "/color".to_string(),
+        vec![
+            OscType::Float(0.2),
+            OscType::Float(0.4),
+            OscType::Int(1),
+            OscType::Float(0.8),
+        ],
+    )
+    .unwrap();
+    assert!(root
+        .set_value("/color".to_string(), vec![OscType::Float(0.2)])
+        .is_err());
+}
+
+#[test]
+fn tagged_nodes() {
+    let mut root = OSCNode::root(None);
+    let param = |path: &str| OscQueryParameter::new(path.to_string(), OscType::Float(0.0));
+    root.add(param("/light/1/dimmer").with_tags(["lighting", "scene-3"]))
+        .unwrap();
+    root.add(param("/light/2/dimmer").with_tag("lighting"))
+        .unwrap();
+    root.add(param("/audio/gain").with_tag("critical-path"))
+        .unwrap();
+
+    let paths = |tag| -> Vec<&str> {
+        root.find_tagged(tag)
+            .iter()
+            .map(|n| n.full_path())
+            .collect()
+    };
+    assert_eq!(paths("lighting"), ["/light/1/dimmer", "/light/2/dimmer"]);
+    assert_eq!(paths("scene-3"), ["/light/1/dimmer"]);
+    assert!(paths("video").is_empty());
+
+    let json = serde_json::to_string(root.get("/light/1/dimmer".to_string()).unwrap()).unwrap();
+    assert!(json.contains(r#""TAGS":["lighting","scene-3"]"#));
+    let deserialized: OSCNode =
+        serde_json::from_str(&serde_json::to_string(&root).unwrap()).unwrap();
+    assert_eq!(deserialized.find_tagged("lighting").len(), 2);
+}
+
+#[test]
+fn critical_parameters() {
+    let mut root = OSCNode::root(None);
+    root.add(OscQueryParameter::new("/scene/go".to_string(), OscType::Int(0)).with_critical(true))
+        .unwrap();
+    root.add(OscQueryParameter::new(
+        "/mixer/gain".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+
+    let node = root.get("/scene/go".to_string()).unwrap();
+    assert!(node.is_critical());
+    assert!(serde_json::to_string(node)
+        .unwrap()
+        .contains(r#""CRITICAL":true"#));
+    let node = root.get("/mixer/gain".to_string()).unwrap();
+    assert!(!node.is_critical());
+    assert!(!serde_json::to_string(node).unwrap().contains("CRITICAL"));
+
+    let json = r#"{"FULL_PATH":"/a","TYPE":"i","CRITICAL":true}"#;
+    assert!(serde_json::from_str::<OSCNode>(json).unwrap().is_critical());
+}
+
+#[test]
+fn clipmode_enforcement() {
+    let mut root = OSCNode::root(None);
+    root.add(
+        OscQueryParameter::new("/gain".to_string(), OscType::Float(0.0))
+            .with_min_max(0.0, 1.0)
+            .with_clipmode(ClipMode::Both)
+            .with_argument(
+                OscQueryArgument::new(OscType::Int(0))
+                    .with_min_max(0.0, 10.0)
+                    .with_clipmode(ClipMode::Low),
+            )
+            .with_argument(OscQueryArgument::new(OscType::Int(0)).with_min_max(0.0, 10.0)),
+    )
+    .unwrap();
+    root.add(
+        OscQueryParameter::array("/eq".to_string(), vec![OscType::Double(0.0); 2])
+            .with_min_max(-12.0, 12.0)
+            .with_clipmode(ClipMode::High),
+    )
+    .unwrap();
+    root.add(
+        OscQueryParameter::new("/blend".to_string(), "normal".into())
+            .with_vals(["normal", "add"])
+            .unwrap()
+            .with_clipmode(ClipMode::Both),
+    )
+    .unwrap();
+
+    let mut set = |path: &str, values| {
+        root.set_value(path.to_string(), values)?;
+        root.value(path.to_string()).map(|values| values.to_vec())
+    };
+    assert_eq!(
+        set(
+            "/gain",
+            vec![OscType::Float(1.5), OscType::Int(-1), OscType::Int(-1)]
+        )
+        .unwrap(),
+        [OscType::Float(1.0), OscType::Int(0), OscType::Int(-1)]
+    );
+    assert_eq!(
+        set(
+            "/gain",
+            vec![OscType::Float(-0.5), OscType::Int(20), OscType::Int(20)]
+        )
+        .unwrap(),
+        [OscType::Float(0.0), OscType::Int(20), OscType::Int(20)]
+    );
+    assert_eq!(
+        set(
+            "/eq",
+            vec![OscType::Array(rosc::OscArray {
+                content: vec![OscType::Double(-20.0), OscType::Double(20.0)]
+            })]
+        )
+        .unwrap(),
+        [OscType::Array(rosc::OscArray {
+            content: vec![OscType::Double(-20.0), OscType::Double(12.0)]
+        })]
+    );
+    assert!(set("/blend", vec!["add".into()]).is_ok());
+    assert!(set("/blend", vec!["subtract".into()]).is_err());
+    assert_eq!(root.value("/blend".to_string()).unwrap(), &["add".into()]);
+}
+
+#[test]
+fn numeric_bounds_convert() {
+    let mut root = OSCNode::root(None);
+    root.add(
+        OscQueryParameter::new("/gain".to_string(), OscType::Float(0.5))
+            .with_min(0)
+            .unwrap()
+            .with_max(1i64)
+            .unwrap()
+            .with_clipmode(ClipMode::Both),
+    )
+    .unwrap();
+    root.add(
+        OscQueryParameter::new("/speed".to_string(), OscType::Float(1.0))
+            .with_vals([1, 2])
+            .unwrap()
+            .with_clipmode(ClipMode::Both),
+    )
+    .unwrap();
+
+    // int bounds of a float parameter are stored as floats and clip its values
+    let json = serde_json::to_string(&root).unwrap();
+    assert!(json.contains(r#""RANGE":[{"MIN":0.0,"MAX":1.0}]"#));
+    assert!(json.contains(r#""RANGE":[{"VALS":[1.0,2.0]}]"#));
+    root.set_value("/gain".to_string(), vec![OscType::Float(3.0)])
+        .unwrap();
+    assert_eq!(
+        root.value("/gain".to_string()).unwrap(),
+        &[OscType::Float(1.0)]
+    );
+    root.set_value("/speed".to_string(), vec![OscType::Float(2.0)])
+        .unwrap();
+}
+
+#[test]
+fn mismatched_bounds() {
+    let gain = || OscQueryParameter::new("/gain".to_string(), OscType::Float(0.5));
+    assert!(matches!(gain().with_min("low"), Err(OscError::BadArg(_))));
+    assert!(gain().with_max(true).is_err());
+    assert!(gain()
+        .with_vals([OscType::Float(1.0), OscType::Nil])
+        .is_err());
+    // numbers are only bounds of numbers, and have to fit the type of the value
+    let name = || OscQueryParameter::new("/name".to_string(), "abc".into());
+    assert!(name().with_min(1).is_err());
+    let index = || OscQueryParameter::new("/index".to_string(), OscType::Int(0));
+    assert!(index().with_max(i64::MAX).is_err());
+    assert!(index().with_vals([1.0, f32::NAN]).is_err());
+
+    // MIN and MAX are not given to strings and bools
+    let mut root = OSCNode::root(None);
+    root.add(name().with_min_max(0.0, 1.0)).unwrap();
+    root.add(
+        OscQueryParameter::new("/mute".to_string(), OscType::Bool(false)).with_min_max(0.0, 1.0),
+    )
+    .unwrap();
+    let json = serde_json::to_string(&root).unwrap();
+    assert!(!json.contains("MIN") && !json.contains("MAX"), "{}", json);
+}
+
+#[test]
+fn extended_types() {
+    let mut root = OSCNode::root(None);
+    root.add(
+        OscQueryParameter::array("/source/pos".to_string(), vec![OscType::Float(0.0); 3])
+            .with_extended_type(OSCExtendedType::PositionCartesianXYZ)
+            .with_argument(
+                OscQueryArgument::new("".into()).with_extended_type("vendor.label".into()),
+            )
+            .with_argument(OscQueryArgument::new(OscType::Int(0))),
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(root.get("/source/pos".to_string()).unwrap()).unwrap();
+    assert!(json.contains(r#""EXTENDED_TYPE":["position.cartesian.xyz","vendor.label",null]"#));
+    let deserialized: OSCNode = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        deserialized.extended_type,
+        Some(vec![
+            Some(OSCExtendedType::PositionCartesianXYZ),
+            Some(OSCExtendedType::Custom("vendor.label".to_string())),
+            None
+        ])
+    );
+}
+
+#[test]
+fn serialize_enabled_extensions() {
+    let param = || {
+        OscQueryParameter::new("/gain".to_string(), OscType::Float(0.5))
+            .with_description("Gain".to_string())
+            .with_access(OSCAccess::ReadWrite)
+            .with_min_max(0.0, 1.0)
+            .with_unit(OSCUnit::Gain(crate::OSCGain::Linear))
+    };
+    let info = || OscHostInfo::new("Test".to_string(), "127.0.0.1".to_string(), 9000);
+
+    let mut root = OSCNode::root(Some(Box::new(info().with_ext_value().with_ext_range())));
+    root.add(param()).unwrap();
+    let json = serde_json::to_string(&root).unwrap();
+    assert!(json.contains(
+        r#"{"FULL_PATH":"/gain","TYPE":"f","VALUE":[0.5],"RANGE":[{"MIN":0.0,"MAX":1.0}]}"#
+    ));
+    assert!(!json.contains(r#""DESCRIPTION":""#) && !json.contains(r#""ACCESS":0"#));
+
+    // extensions derived from the tree are advertised in HOST_INFO as well
+    let mut root = OSCNode::root(Some(Box::new(info().with_auto_extensions())));
+    root.add(param()).unwrap();
+    let json = serde_json::to_string(&root).unwrap();
+    assert!(json.contains(r#""DESCRIPTION":"Gain","FULL_PATH":"/gain","ACCESS":3"#));
+    assert!(json.contains(r#""UNIT":["gain.linear"]"#));
+    assert!(json.contains(r#""VALUE":true,"RANGE":true,"DESCRIPTION":true,"TAGS":false"#));
+
+    // without HOST_INFO everything is serialized
+    let mut root = OSCNode::root(None);
+    root.add(param()).unwrap();
+    let json = serde_json::to_string(&root).unwrap();
+    assert!(json.contains(r#""DESCRIPTION":"Gain""#) && json.contains("UNIT"));
+}
+
+#[test]
+fn fill_host_address() {
+    let bound: SocketAddr = ([127, 0, 0, 1], 9000).into();
+    // port 0 asks for an ephemeral port, which is advertised once bound
+    let info = OscHostInfo::new("OSCQuery Test".to_string(), "127.0.0.1".to_string(), 0);
+    let mut root = OSCNode::root(Some(Box::new(info)));
+    root.fill_host_info(bound, OscTransport::Udp);
+    assert_eq!(root.osc_address(), bound);
+    // given addresses are kept, an unspecified IP is not advertised
+    root.fill_host_info(([0, 0, 0, 0], 9001).into(), OscTransport::Tcp);
+    assert_eq!(root.osc_address(), bound);
+    let mut root = OSCNode::root(Some(Box::new(OscHostInfo::from_name(
+        "OSCQuery Test".to_string(),
+    ))));
+    root.fill_host_info(([0, 0, 0, 0], 9001).into(), OscTransport::Tcp);
+    let host_info = root.host_info().unwrap();
+    assert_eq!(host_info.osc_ip, None);
+    assert_eq!(host_info.osc_port, Some(9001));
+    assert_eq!(host_info.osc_trans, Some(OscTransport::Tcp));
+}
+
+#[test]
+fn match_patterns() {
+    let mut root = OSCNode::root(None);
+    for path in [
+        "/mixer/ch1/gain",
+        "/mixer/ch2/gain",
+        "/mixer/ch10/gain",
+        "/mixer/master/gain",
+        "/fx/reverb/mix",
+        "/fx/delay/mix",
+        "/fx/delay/time",
+    ] {
+        root.add(OscQueryParameter::new(
+            path.to_string(),
+            OscType::Float(0.0),
+        ))
+        .unwrap();
+    }
+    let paths =
+        |pattern| -> Vec<&str> { root.match_pattern(pattern).map(|n| n.full_path()).collect() };
+
+    assert_eq!(paths("/mixer/ch1/gain"), ["/mixer/ch1/gain"]);
+    assert_eq!(
+        paths("/mixer/ch*/gain"),
+        ["/mixer/ch1/gain", "/mixer/ch10/gain", "/mixer/ch2/gain"]
+    );
+    assert_eq!(
+        paths("/mixer/ch?/gain"),
+        ["/mixer/ch1/gain", "/mixer/ch2/gain"]
+    );
+    assert_eq!(paths("/mixer/ch[2-9]/gain"), ["/mixer/ch2/gain"]);
+    assert_eq!(paths("/mixer/ch[!2]/gain"), ["/mixer/ch1/gain"]);
+    assert_eq!(paths("/mixer/*/gain").len(), 4);
+    assert_eq!(
+        paths("/fx/{reverb,delay}/mix"),
+        ["/fx/delay/mix", "/fx/reverb/mix"]
+    );
+    assert_eq!(paths("//mix"), ["/fx/delay/mix", "/fx/reverb/mix"]);
+    assert_eq!(paths("/fx//time"), ["/fx/delay/time"]);
+    assert_eq!(paths("//delay//time"), ["/fx/delay/time"]);
+    assert_eq!(paths("/fx/"), ["/fx"]);
+    assert_eq!(paths("/"), ["/"]);
+    assert!(paths("/mixer/ch[1/gain").is_empty());
+    assert!(paths("mixer").is_empty());
+}
+
+#[test]
+fn match_pathological_patterns() {
+    let mut root = OSCNode::root(None);
+    let name = "a".repeat(200);
+    root.add(OscQueryParameter::new(
+        format!("/{}", name),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+
+    // patterns from the network must not take exponential time or deep recursion
+    let start = std::time::Instant::now();
+    let stars = format!("/{}b", "*a".repeat(40));
+    assert_eq!(root.match_pattern(&stars).count(), 0);
+    let empty_choices = format!("/{}{}", "{,a}".repeat(10_000), "*");
+    assert_eq!(root.match_pattern(&empty_choices).count(), 1);
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[test]
+fn match_repeated_slashes() {
+    let mut root = OSCNode::root(None);
+    let path: String = "abcdefgh".chars().map(|c| format!("/{}", c)).collect();
+    root.add(OscQueryParameter::new(path.clone(), OscType::Float(0.0)))
+        .unwrap();
+
+    // every way of splitting the slashes between the levels must not be explored separately
+    let start = std::time::Instant::now();
+    assert_eq!(
+        root.match_pattern(&format!("{}x", "/".repeat(200))).count(),
+        0
+    );
+    let found: Vec<_> = root
+        .match_pattern(&format!("{}h", "/".repeat(200)))
+        .map(|node| node.full_path())
+        .collect();
+    assert_eq!(found, [path.as_str()]);
+    assert_eq!(root.match_pattern(&"/a".repeat(5_000)).count(), 0);
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+}
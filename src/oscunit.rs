@@ -282,18 +282,16 @@ impl<'de> Deserialize<'de> for OSCUnit {
                     ))
                 }
             })),
-            _ => {
-                return Err(serde::de::Error::unknown_variant(
-                    parts.concat().as_str(),
-                    &[
-                        "distance.<..>",
-                        "angle<..>",
-                        "gain<..>",
-                        "time<..>",
-                        "speed<..>",
-                    ],
-                ))
-            }
+            _ => Err(serde::de::Error::unknown_variant(
+                parts.concat().as_str(),
+                &[
+                    "distance.<..>",
+                    "angle<..>",
+                    "gain<..>",
+                    "time<..>",
+                    "speed<..>",
+                ],
+            )),
         }
     }
 }
@@ -0,0 +1,641 @@
+use crate::{ChangeSource, OscFraming, OscTransport, OscTreeHandle};
+use rosc::{OscBundle, OscMessage, OscPacket, OscTime};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, UdpSocket};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// SLIP special bytes (RFC 1055)
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// largest OSC packet accepted over TCP
+const MAX_FRAME: usize = 1 << 20;
+
+/// largest UDP payload (over IPv4), OSC packets are often larger than the MTU
+const MAX_DATAGRAM: usize = 65507;
+
+/// most bundles of a tree waiting for their timetag at the same time
+const MAX_PENDING_BUNDLES: usize = 1024;
+
+/// how far in the future the timetag of a bundle can be
+const MAX_BUNDLE_DELAY: Duration = Duration::from_secs(60);
+
+/// the special timetag of bundles that are applied as soon as they are received
+const IMMEDIATELY: OscTime = OscTime {
+    seconds: 0,
+    fractional: 1,
+};
+
+/// Binds an OSC server to the `OSC_IP`/`OSC_PORT` of the `HOST_INFO` of the tree
+/// and spawns the task receiving OSC packets on it.
+/// The server uses UDP, or TCP if the host information was created `with_tcp`.
+///
+/// Every received message is applied to the tree with `OscTreeHandle::apply_message`,
+/// address patterns are dispatched to all matching parameters.
+/// Bundles are applied atomically, either immediately or at the time of their timetag.
+/// At most 1024 bundles can wait for their timetag, which can be at most 60 seconds ahead,
+/// other bundles are dropped.
+/// Messages for unknown, read only or differently typed parameters are dropped
+/// and reported to the callbacks registered with `OscTreeHandle::on_rejected`.
+/// A missing `OSC_IP`/`OSC_PORT` (or port 0) is filled in with the address the server is bound to,
+/// the OSCQuery service advertises it from then on.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the OSCNode tree to update, or an `OscTreeHandle` to it.
+pub async fn run_osc_receiver(
+    root: impl Into<OscTreeHandle>,
+) -> tokio::io::Result<tokio::task::JoinHandle<()>> {
+    let tree = root.into();
+    let address = tree.read().osc_address();
+    let transport = tree.read().osc_transport();
+    match transport {
+        (OscTransport::Udp, _) => run_udp_receiver(tree, address).await,
+        (OscTransport::Tcp, framing) => run_tcp_receiver(tree, address, framing).await,
+    }
+}
+
+async fn run_udp_receiver(
+    tree: OscTreeHandle,
+    address: SocketAddr,
+) -> tokio::io::Result<tokio::task::JoinHandle<()>> {
+    println!("oscq_rs start osc udp at {:?}", address);
+    let socket = UdpSocket::bind(address).await?;
+    let address = socket.local_addr()?;
+    tree.fill_host_info(address, OscTransport::Udp);
+    println!("oscq_rs started osc udp at {:?}", address);
+
+    let handle = tokio::task::spawn(async move {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            let (len, peer) = match socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(err) => {
+                    println!("oscq_rs osc udp error: {:?}", err);
+                    continue;
+                }
+            };
+            apply_frame(&tree, &buf[..len], ChangeSource::Osc(peer));
+        }
+    });
+
+    Ok(handle)
+}
+
+async fn run_tcp_receiver(
+    tree: OscTreeHandle,
+    address: SocketAddr,
+    framing: OscFraming,
+) -> tokio::io::Result<tokio::task::JoinHandle<()>> {
+    println!("oscq_rs start osc tcp at {:?}", address);
+    let listener = TcpListener::bind(address).await?;
+    let address = listener.local_addr()?;
+    tree.fill_host_info(address, OscTransport::Tcp);
+    println!("oscq_rs started osc tcp at {:?}", address);
+
+    let handle = tokio::task::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    println!("oscq_rs osc tcp error: {:?}", err);
+                    continue;
+                }
+            };
+            let tree = tree.clone();
+            tokio::task::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                loop {
+                    let frame = match framing {
+                        OscFraming::LengthPrefix => read_length_prefixed(&mut stream).await,
+                        OscFraming::Slip => read_slip(&mut stream).await,
+                    };
+                    match frame {
+                        Ok(frame) => apply_frame(&tree, &frame, ChangeSource::Osc(peer)),
+                        Err(_) => break,
+                    }
+                }
+                println!("oscq_rs osc tcp closed {:?}", peer);
+            });
+        }
+    });
+
+    Ok(handle)
+}
+
+/// read an OSC 1.0 packet that is preceded by its size
+async fn read_length_prefixed(stream: &mut (impl AsyncRead + Unpin)) -> tokio::io::Result<Vec<u8>> {
+    let len = stream.read_u32().await? as usize;
+    if len > MAX_FRAME {
+        return Err(tokio::io::Error::new(
+            tokio::io::ErrorKind::InvalidData,
+            format!("osc packet of {} bytes is too large", len),
+        ));
+    }
+    let mut frame = vec![0; len];
+    stream.read_exact(&mut frame).await?;
+    Ok(frame)
+}
+
+/// read an OSC 1.1 SLIP encoded packet, empty frames between two END bytes are skipped
+async fn read_slip(stream: &mut (impl AsyncRead + Unpin)) -> tokio::io::Result<Vec<u8>> {
+    let mut frame = Vec::new();
+    loop {
+        match stream.read_u8().await? {
+            SLIP_END if frame.is_empty() => {}
+            SLIP_END => return Ok(frame),
+            SLIP_ESC => match stream.read_u8().await? {
+                SLIP_ESC_END => frame.push(SLIP_END),
+                SLIP_ESC_ESC => frame.push(SLIP_ESC),
+                byte => frame.push(byte),
+            },
+            byte => frame.push(byte),
+        }
+        if frame.len() > MAX_FRAME {
+            return Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::InvalidData,
+                "osc packet is too large",
+            ));
+        }
+    }
+}
+
+/// decode a received OSC packet and apply it to the tree
+pub(crate) fn apply_frame(tree: &OscTreeHandle, frame: &[u8], source: ChangeSource) {
+    match rosc::decoder::decode_udp(frame) {
+        Ok((_, packet)) => apply_packet(tree, packet, source),
+        Err(err) => println!("oscq_rs invalid osc packet from {:?}: {:?}", source, err),
+    }
+}
+
+/// apply all messages of the packet to the tree.
+/// The messages of a bundle and of its nested bundles with the same timetag are applied
+/// atomically at the time of the timetag, nested bundles with another timetag are applied
+/// on their own.
+pub(crate) fn apply_packet(tree: &OscTreeHandle, packet: OscPacket, source: ChangeSource) {
+    match packet {
+        OscPacket::Message(msg) => {
+            if let Err(err) = tree.apply_message(&msg, source) {
+                println!("oscq_rs dropped osc message from {:?}: {}", source, err);
+            }
+        }
+        OscPacket::Bundle(bundle) => {
+            let mut groups = Vec::new();
+            collect_bundle(bundle, &mut groups);
+            for (timetag, messages) in groups {
+                let result = match bundle_delay(timetag) {
+                    None => tree
+                        .apply_messages(&messages, source)
+                        .map_err(|err| err.to_string()),
+                    Some(delay) => tree.bundles().schedule(tree, delay, messages, source),
+                };
+                if let Err(err) = result {
+                    println!("oscq_rs dropped osc bundle from {:?}: {}", source, err);
+                }
+            }
+        }
+    }
+}
+
+/// collect the messages of `bundle` and its nested bundles, grouped by their timetag
+fn collect_bundle(bundle: OscBundle, groups: &mut Vec<(OscTime, Vec<OscMessage>)>) {
+    let group = match groups
+        .iter()
+        .position(|(timetag, _)| *timetag == bundle.timetag)
+    {
+        Some(group) => group,
+        None => {
+            groups.push((bundle.timetag, Vec::new()));
+            groups.len() - 1
+        }
+    };
+    for packet in bundle.content {
+        match packet {
+            OscPacket::Message(msg) => groups[group].1.push(msg),
+            OscPacket::Bundle(nested) => collect_bundle(nested, groups),
+        }
+    }
+}
+
+/// The bundles of a tree that wait for their timetag.
+/// A single task applies them in the order of their timetags,
+/// it runs only while there are bundles waiting.
+#[derive(Debug, Default)]
+pub(crate) struct BundleScheduler {
+    state: Mutex<SchedulerState>,
+    wake: Notify,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    pending: BinaryHeap<ScheduledBundle>,
+    running: bool,
+    // keeps bundles with the same timetag in the order they were received
+    sequence: u64,
+}
+
+#[derive(Debug)]
+struct ScheduledBundle {
+    due: Instant,
+    sequence: u64,
+    messages: Vec<OscMessage>,
+    source: ChangeSource,
+}
+
+// `BinaryHeap` is a max heap, the bundle that is due first has to be the greatest
+impl Ord for ScheduledBundle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.due, other.sequence).cmp(&(self.due, self.sequence))
+    }
+}
+
+impl PartialOrd for ScheduledBundle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for ScheduledBundle {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledBundle {}
+
+impl BundleScheduler {
+    /// apply the `messages` of a bundle to the tree after `delay`,
+    /// fails if the delay is too long or too many bundles are waiting already
+    fn schedule(
+        &self,
+        tree: &OscTreeHandle,
+        delay: Duration,
+        messages: Vec<OscMessage>,
+        source: ChangeSource,
+    ) -> Result<(), String> {
+        if delay > MAX_BUNDLE_DELAY {
+            return Err(format!("timetag is {:?} ahead", delay));
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.pending.len() >= MAX_PENDING_BUNDLES {
+            return Err(format!(
+                "{} bundles are waiting already",
+                state.pending.len()
+            ));
+        }
+        state.sequence += 1;
+        let bundle = ScheduledBundle {
+            due: Instant::now() + delay,
+            sequence: state.sequence,
+            messages,
+            source,
+        };
+        state.pending.push(bundle);
+        if state.running {
+            self.wake.notify_one();
+        } else {
+            state.running = true;
+            tokio::task::spawn(run_scheduler(tree.clone()));
+        }
+        Ok(())
+    }
+
+    /// number of bundles waiting for their timetag
+    #[cfg(test)]
+    fn pending(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+}
+
+/// apply the bundles of the tree when they are due, until none is waiting anymore
+async fn run_scheduler(tree: OscTreeHandle) {
+    let scheduler = tree.bundles();
+    loop {
+        // the lock is released before waiting
+        let due = {
+            let mut state = scheduler.state.lock().unwrap();
+            match state.pending.peek().map(|bundle| bundle.due) {
+                None => {
+                    state.running = false;
+                    return;
+                }
+                Some(due) if due <= Instant::now() => Err(state.pending.pop().unwrap()),
+                Some(due) => Ok(due),
+            }
+        };
+        match due {
+            Ok(due) => {
+                // a new bundle can be due earlier
+                tokio::select! {
+                    _ = tokio::time::sleep_until(due) => {}
+                    _ = scheduler.wake.notified() => {}
+                }
+            }
+            Err(bundle) => {
+                if let Err(err) = tree.apply_messages(&bundle.messages, bundle.source) {
+                    println!(
+                        "oscq_rs dropped osc bundle from {:?}: {}",
+                        bundle.source, err
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// the time until a bundle with the `timetag` is due,
+/// `None` for the immediate timetag (1) and timetags in the past
+fn bundle_delay(timetag: OscTime) -> Option<Duration> {
+    if timetag == IMMEDIATELY {
+        return None;
+    }
+    SystemTime::from(timetag)
+        .duration_since(SystemTime::now())
+        .ok()
+}
+
+#[tokio::test]
+async fn test_osc_receiver() {
+    use crate::{OSCAccess, OSCNode, OscHostInfo, OscQueryParameter, TreeEvent};
+    use rosc::{OscMessage, OscType};
+    use std::time::Duration;
+
+    let info = OscHostInfo::from_name("OSCQuery Test".to_string());
+    let tree = OscTreeHandle::new(OSCNode::root(Some(Box::new(info))));
+    tree.add(
+        OscQueryParameter::new("/mixer/gain".to_string(), OscType::Float(0.0))
+            .with_access(OSCAccess::ReadWrite),
+    )
+    .unwrap();
+    tree.add(
+        OscQueryParameter::new("/mixer/meter".to_string(), OscType::Float(0.0))
+            .with_access(OSCAccess::Read),
+    )
+    .unwrap();
+    let (received_tx, mut received) = tokio::sync::mpsc::unbounded_channel();
+    tree.on_message(move |msg| received_tx.send(msg.addr.clone()).unwrap());
+    let mut events = tree.subscribe();
+
+    let _receiver = run_osc_receiver(tree.clone()).await.unwrap();
+    // the bound port is advertised in HOST_INFO
+    let port = tree.read().osc_address().port();
+    assert_ne!(port, 0);
+
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let send = |addr: &str, args| {
+        let packet = OscPacket::Message(OscMessage {
+            addr: addr.to_string(),
+            args,
+        });
+        rosc::encoder::encode(&packet).unwrap()
+    };
+    let target: SocketAddr = ([127, 0, 0, 1], port).into();
+    // read only, wrong type and unknown addresses are dropped
+    for buf in [
+        send("/mixer/meter", vec![OscType::Float(1.0)]),
+        send("/mixer/gain", vec![OscType::Int(1)]),
+        send("/mixer/mute", vec![OscType::Bool(true)]),
+        send("/mixer/gain", vec![OscType::Float(0.5)]),
+    ] {
+        socket.send_to(&buf, target).await.unwrap();
+    }
+
+    let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        event,
+        TreeEvent::ValueChanged {
+            path: "/mixer/gain".to_string(),
+            value: vec![OscType::Float(0.5)],
+            source: ChangeSource::Osc(socket.local_addr().unwrap()),
+        }
+    );
+    assert_eq!(
+        tree.value("/mixer/meter".to_string()).unwrap(),
+        vec![OscType::Float(0.0)]
+    );
+    // the callback runs right after the event was published, only for the applied message
+    let addr = tokio::time::timeout(Duration::from_secs(1), received.recv())
+        .await
+        .unwrap();
+    assert_eq!(addr.as_deref(), Some("/mixer/gain"));
+    assert!(received.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_osc_large_datagram() {
+    use crate::{OSCNode, OscHostInfo, OscQueryParameter, TreeEvent};
+    use rosc::OscType;
+    use std::time::Duration;
+
+    let info = OscHostInfo::from_name("OSCQuery Test".to_string());
+    let tree = OscTreeHandle::new(OSCNode::root(Some(Box::new(info))));
+    tree.add(OscQueryParameter::new(
+        "/data".to_string(),
+        OscType::String(String::new()),
+    ))
+    .unwrap();
+    tree.add(OscQueryParameter::new("/done".to_string(), OscType::Int(0)))
+        .unwrap();
+    let mut events = tree.subscribe();
+    let _receiver = run_osc_receiver(tree.clone()).await.unwrap();
+    let port = tree.read().osc_address().port();
+
+    let data = "x".repeat(4000);
+    let packet = OscPacket::Bundle(OscBundle {
+        timetag: IMMEDIATELY,
+        content: vec![
+            OscPacket::Message(OscMessage {
+                addr: "/data".to_string(),
+                args: vec![OscType::String(data.clone())],
+            }),
+            OscPacket::Message(OscMessage {
+                addr: "/done".to_string(),
+                args: vec![OscType::Int(1)],
+            }),
+        ],
+    });
+    let buf = rosc::encoder::encode(&packet).unwrap();
+    assert!(buf.len() > rosc::decoder::MTU);
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    socket
+        .send_to(&buf, SocketAddr::from(([127, 0, 0, 1], port)))
+        .await
+        .unwrap();
+
+    // the whole bundle is applied, not a truncated part of it
+    for path in ["/data", "/done"] {
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, TreeEvent::ValueChanged { path: changed, .. } if changed == path));
+    }
+    assert_eq!(
+        tree.value("/data".to_string()).unwrap(),
+        vec![OscType::String(data)]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_osc_bundles() {
+    use crate::{OSCNode, OscQueryParameter};
+    use rosc::OscType;
+
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    for path in ["/a", "/b", "/c"] {
+        tree.add(OscQueryParameter::new(path.to_string(), OscType::Int(0)))
+            .unwrap();
+    }
+    let peer = ChangeSource::Osc(([127, 0, 0, 1], 9000).into());
+    let message = |addr: &str, value| {
+        OscPacket::Message(OscMessage {
+            addr: addr.to_string(),
+            args: vec![OscType::Int(value)],
+        })
+    };
+    let bundle = |timetag, a, b| {
+        OscPacket::Bundle(OscBundle {
+            timetag,
+            content: vec![message("/a", a), message("/b", b)],
+        })
+    };
+    let at = |delay: Duration| OscTime::try_from(SystemTime::now() + delay).unwrap();
+    let values = || {
+        (
+            tree.value("/a".to_string()).unwrap(),
+            tree.value("/b".to_string()).unwrap(),
+        )
+    };
+    // let the scheduler run after the time was advanced
+    let advance = |duration| async move {
+        tokio::time::advance(duration).await;
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    };
+
+    apply_packet(&tree, bundle(IMMEDIATELY, 1, 2), peer);
+    assert_eq!(values(), (vec![OscType::Int(1)], vec![OscType::Int(2)]));
+
+    apply_packet(&tree, bundle(at(Duration::from_millis(300)), 3, 4), peer);
+    advance(Duration::from_millis(200)).await;
+    assert_eq!(values(), (vec![OscType::Int(1)], vec![OscType::Int(2)]));
+    advance(Duration::from_millis(200)).await;
+    assert_eq!(values(), (vec![OscType::Int(3)], vec![OscType::Int(4)]));
+    assert_eq!(tree.bundles().pending(), 0);
+
+    // timetags in the past are applied right away
+    let past = SystemTime::now() - Duration::from_secs(1);
+    apply_packet(&tree, bundle(OscTime::try_from(past).unwrap(), 5, 6), peer);
+    assert_eq!(values(), (vec![OscType::Int(5)], vec![OscType::Int(6)]));
+
+    // a nested bundle with the same timetag is part of the same transaction,
+    // so the invalid message in it drops the whole bundle
+    let nested = OscPacket::Bundle(OscBundle {
+        timetag: IMMEDIATELY,
+        content: vec![
+            message("/a", 7),
+            OscPacket::Bundle(OscBundle {
+                timetag: IMMEDIATELY,
+                content: vec![message("/missing", 8)],
+            }),
+        ],
+    });
+    apply_packet(&tree, nested, peer);
+    assert_eq!(values().0, vec![OscType::Int(5)]);
+
+    // bundles too far ahead and bundles beyond the limit are dropped
+    apply_packet(&tree, bundle(at(MAX_BUNDLE_DELAY * 2), 9, 9), peer);
+    assert_eq!(tree.bundles().pending(), 0);
+    for _ in 0..MAX_PENDING_BUNDLES + 10 {
+        let later = OscPacket::Bundle(OscBundle {
+            timetag: at(Duration::from_secs(1)),
+            content: vec![message("/c", 1)],
+        });
+        apply_packet(&tree, later, peer);
+    }
+    assert_eq!(tree.bundles().pending(), MAX_PENDING_BUNDLES);
+    advance(Duration::from_secs(2)).await;
+    assert_eq!(tree.bundles().pending(), 0);
+    assert_eq!(tree.value("/c".to_string()).unwrap(), vec![OscType::Int(1)]);
+}
+
+#[tokio::test]
+async fn test_osc_tcp_receiver() {
+    use crate::{OSCNode, OscHostInfo, OscQueryParameter, TreeEvent};
+    use rosc::{OscMessage, OscType};
+    use tokio::io::AsyncWriteExt;
+
+    for framing in [OscFraming::LengthPrefix, OscFraming::Slip] {
+        let info = OscHostInfo::from_name("OSCQuery Test".to_string()).with_tcp(framing);
+        let tree = OscTreeHandle::new(OSCNode::root(Some(Box::new(info))));
+        tree.add(OscQueryParameter::new(
+            "/mixer/gain".to_string(),
+            OscType::Float(0.0),
+        ))
+        .unwrap();
+        let mut events = tree.subscribe();
+        let _receiver = run_osc_receiver(tree.clone()).await.unwrap();
+        let host_info = serde_json::to_string(&tree.read().host_info()).unwrap();
+        assert!(host_info.contains(r#""OSC_TRANSPORT":"TCP""#));
+
+        let packet = |value| {
+            rosc::encoder::encode(&OscPacket::Message(OscMessage {
+                addr: "/mixer/gain".to_string(),
+                // 0xC0 and 0xDB in the float have to be escaped by SLIP
+                args: vec![OscType::Float(value)],
+            }))
+            .unwrap()
+        };
+        let mut stream = Vec::new();
+        for value in [f32::from_be_bytes([0xC0, 0xDB, 0, 0]), 0.5] {
+            let packet = packet(value);
+            match framing {
+                OscFraming::LengthPrefix => {
+                    stream.extend((packet.len() as u32).to_be_bytes());
+                    stream.extend(packet);
+                }
+                OscFraming::Slip => {
+                    stream.push(SLIP_END);
+                    for byte in packet {
+                        match byte {
+                            SLIP_END => stream.extend([SLIP_ESC, SLIP_ESC_END]),
+                            SLIP_ESC => stream.extend([SLIP_ESC, SLIP_ESC_ESC]),
+                            byte => stream.push(byte),
+                        }
+                    }
+                    stream.push(SLIP_END);
+                }
+            }
+        }
+        let port = tree.read().osc_address().port();
+        let mut client = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        client.write_all(&stream).await.unwrap();
+
+        for value in [f32::from_be_bytes([0xC0, 0xDB, 0, 0]), 0.5] {
+            let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            match event {
+                TreeEvent::ValueChanged { value: args, .. } => {
+                    assert_eq!(args, vec![OscType::Float(value)])
+                }
+                other => panic!("expected a value change, got {:?}", other),
+            }
+        }
+    }
+}
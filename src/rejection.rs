@@ -0,0 +1,71 @@
+use crate::OSCAccess;
+use rosc::{OscError, OscType};
+
+/// The reason why a write to the VALUE of a parameter was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteRejection {
+    /// there is no parameter at the address, or none matches the address pattern
+    NotFound(String),
+    /// the ACCESS of the parameter does not allow writing (`Read` or `NoAcces`)
+    NotWritable { path: String, access: OSCAccess },
+    /// the arguments do not match the TYPE of the parameter
+    TypeMismatch { path: String, args: Vec<OscType> },
+    /// the arguments are not within the RANGE of the parameter, e.g. not one of its VALS
+    OutOfRange { path: String, reason: String },
+}
+
+impl WriteRejection {
+    /// the address of the rejected write
+    pub fn path(&self) -> &str {
+        match self {
+            WriteRejection::NotFound(path)
+            | WriteRejection::NotWritable { path, .. }
+            | WriteRejection::TypeMismatch { path, .. }
+            | WriteRejection::OutOfRange { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for WriteRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteRejection::NotFound(path) => write!(f, "there is no parameter at {}", path),
+            WriteRejection::NotWritable { path, access } => {
+                write!(f, "{} is not writable (ACCESS {:?})", path, access)
+            }
+            WriteRejection::TypeMismatch { path, args } => {
+                write!(f, "{:?} does not match the TYPE of {}", args, path)
+            }
+            WriteRejection::OutOfRange { path, reason } => write!(f, "{} for {}", reason, path),
+        }
+    }
+}
+
+impl std::error::Error for WriteRejection {}
+
+impl From<WriteRejection> for OscError {
+    fn from(rejection: WriteRejection) -> Self {
+        match rejection {
+            WriteRejection::NotFound(path) => OscError::BadAddress(path),
+            rejection => OscError::BadArg(rejection.to_string()),
+        }
+    }
+}
+
+#[test]
+fn rejection_errors() {
+    let rejection = WriteRejection::NotWritable {
+        path: "/meter".to_string(),
+        access: OSCAccess::Read,
+    };
+    assert_eq!(rejection.path(), "/meter");
+    assert_eq!(
+        rejection.to_string(),
+        "/meter is not writable (ACCESS Read)"
+    );
+    assert!(matches!(OscError::from(rejection), OscError::BadArg(_)));
+    assert!(matches!(
+        OscError::from(WriteRejection::NotFound("/foo".to_string())),
+        OscError::BadAddress(path) if path == "/foo"
+    ));
+}
@@ -1,6 +1,8 @@
 use crate::tokiort::TokioIo;
-use crate::OSCNode;
+use crate::websocket::serve_websocket;
+use crate::OscTreeHandle;
 
+use hyper::header::{CONNECTION, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY, UPGRADE};
 use hyper::server::conn::http1;
 use hyper::service::Service;
 use hyper::{body::Incoming as IncomingBody, Request, Response};
@@ -13,20 +15,86 @@ use std::thread::spawn;
 use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::runtime::Runtime;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::WebSocketStream;
 use zeroconf::prelude::*;
 
 /// A Hyper service that implements the OSCQuery protocol.
 ///
 /// This service is responsible for handling HTTP requests that conform to the OSCQuery protocol.
-/// It takes an `OSCNode` as its root, and uses it to respond to requests.
+/// It takes a shared `OSCNode` tree as its root, and uses its current state to respond to requests.
 ///
 /// Implements the `Service` trait from the Hyper crate, which is used to handle incoming requests.
 ///
 /// The `call` method is used to handle each incoming request. It matches the request's path and query
 /// parameters to OSCNode values, and returns a response in JSON format.
+/// WebSocket upgrade requests on the same port are handed over to the LISTEN/IGNORE streaming.
 struct OscQueryStatic {
     /// The root of the OSCNode hierarchy.
-    root: Arc<OSCNode>,
+    root: OscTreeHandle,
+    /// The address of the connected client.
+    peer: SocketAddr,
+}
+
+impl OscQueryStatic {
+    /// Accept a WebSocket upgrade request and spawn the task serving the upgraded connection.
+    fn upgrade_websocket(
+        &self,
+        mut req: Request<IncomingBody>,
+    ) -> Result<Response<String>, hyper::Error> {
+        let key = match req.headers().get(SEC_WEBSOCKET_KEY) {
+            Some(key) => derive_accept_key(key.as_bytes()),
+            None => {
+                return Ok(Response::builder()
+                    .status(400)
+                    .body("missing Sec-WebSocket-Key".to_string())
+                    .unwrap())
+            }
+        };
+
+        let tree = self.root.clone();
+        let peer = self.peer;
+        tokio::task::spawn(async move {
+            match hyper::upgrade::on(&mut req).await {
+                Ok(upgraded) => {
+                    let ws = WebSocketStream::from_raw_socket(
+                        TokioIo::new(upgraded),
+                        Role::Server,
+                        None,
+                    )
+                    .await;
+                    serve_websocket(ws, tree, peer).await;
+                }
+                Err(err) => println!("oscq_rs websocket upgrade failed: {:?}", err),
+            }
+        });
+
+        Ok(Response::builder()
+            .status(101)
+            .header(CONNECTION, "Upgrade")
+            .header(UPGRADE, "websocket")
+            .header(SEC_WEBSOCKET_ACCEPT, key)
+            .body(String::new())
+            .unwrap())
+    }
+}
+
+/// check if the request asks to switch the connection to the WebSocket protocol
+fn is_websocket_upgrade(req: &Request<IncomingBody>) -> bool {
+    let header_contains = |name, value: &str| {
+        req.headers().get_all(name).iter().any(|header| {
+            header
+                .to_str()
+                .map(|header| {
+                    header
+                        .split(',')
+                        .any(|part| part.trim().eq_ignore_ascii_case(value))
+                })
+                .unwrap_or(false)
+        })
+    };
+    header_contains(CONNECTION, "upgrade") && header_contains(UPGRADE, "websocket")
 }
 
 /// Implementation of the `hyper::service::Service` trait for serving OSC query requests.
@@ -50,40 +118,59 @@ impl Service<Request<IncomingBody>> for OscQueryStatic {
                 .unwrap())
         }
 
+        // Create an empty (or plain text) response with the given status code.
+        fn mk_status(status: u16, s: &str) -> Result<Response<String>, hyper::Error> {
+            Ok(Response::builder()
+                .status(status)
+                .body(s.to_string())
+                .unwrap())
+        }
+
         // Log the incoming request method and URI for debugging purposes.
         println!("{:?} {:?}", req.uri(), req.method());
 
+        // Switch to the WebSocket protocol for LISTEN/IGNORE streaming.
+        if is_websocket_upgrade(&req) {
+            let res = self.upgrade_websocket(req);
+            return Box::pin(async { res });
+        }
+
+        // Lock the tree only while the response is built, so the application can keep updating it.
+        let root = self.root.read();
+
         // If the requested OSC node exists, generate an appropriate response based on the query string.
-        if let Ok(node) = self.root.get(req.uri().path().to_string()) {
+        // Only the attributes of the extensions enabled in the HOST_INFO of the root are served.
+        if let Ok(view) = root.view(req.uri().path().to_string()) {
+            let extensions = &view.extensions;
             if let Some(query) = req.uri().query() {
-                let res = match query {
-                    "HOST_INFO" => mk_response(
-                        serde_json::to_value(node)
-                            .unwrap()
-                            .get("HOST_INFO")
-                            .unwrap()
-                            .to_string(),
-                    ),
-                    "VALUE" => mk_response(format!(
-                        "{{\"VALUE\":{}}}",
-                        serde_json::to_value(node).unwrap().get("VALUE").unwrap()
-                    )),
-                    "TYPE" => mk_response(
-                        serde_json::to_value(node)
-                            .unwrap()
-                            .get("TYPE")
-                            .unwrap()
-                            .to_string(),
-                    ),
-                    _ => Ok(Response::builder()
-                        .status(204)
-                        .body("not supported".to_string())
-                        .unwrap()),
+                // Answer a single attribute as {"ATTR": ...}, HOST_INFO is answered as the object itself.
+                // Attributes the node does not have are answered with 204,
+                // unknown attributes and attributes of disabled extensions with 400.
+                let res = match extensions.attribute_enabled(query) {
+                    Some(true) if query == "HOST_INFO" => {
+                        // HOST_INFO describes the whole server and is answered from every path
+                        match root.host_info() {
+                            Some(host_info) => {
+                                mk_response(serde_json::to_string(&host_info).unwrap())
+                            }
+                            None => mk_status(204, ""),
+                        }
+                    }
+                    Some(true) => {
+                        let json = serde_json::to_value(&view).unwrap();
+                        match (query, json.get(query)) {
+                            (_, Some(value)) => {
+                                mk_response(serde_json::json!({ query: value }).to_string())
+                            }
+                            (_, None) => mk_status(204, ""),
+                        }
+                    }
+                    _ => mk_status(400, "Bad Request"),
                 };
                 return Box::pin(async { res });
             } else {
                 // If no query string is present, return the full OSC query data.
-                let res = mk_response(serde_json::to_string(node).unwrap());
+                let res = mk_response(serde_json::to_string(&view).unwrap());
                 return Box::pin(async { res });
             }
         }
@@ -106,34 +193,23 @@ fn on_service_registered(
     println!("Service registered: {:?}", service);
 }
 
-/// Runs an OSCQuery server on the given socket address, serving the OSCNode
-/// rooted at `root`.
-///
-/// # Arguments
-///
-/// * `root` - The root node of the OSCNode tree to serve.
-/// * `address` - The socket address on which to listen for incoming requests.
+/// Spawns the task accepting connections on the bound OSCQuery HTTP `listener`.
 ///
-/// # Returns
-///
-/// Returns a tuple containing two `JoinHandle`s: one for the main service loop,
-/// and one for the Zeroconf service registration loop.
-pub async fn run_oscquery_service(
-    root: OSCNode,
-    address: SocketAddr,
-) -> tokio::io::Result<(tokio::task::JoinHandle<()>, tokio::task::JoinHandle<()>)> {
-    let arc_root = Arc::new(root);
-    println!("oscq_rs start tcp at {:?}", address);
-    let listener = TcpListener::bind(address).await?;
-    println!("oscq_rs started tcp at {:?}", address);
-
-    let handle = tokio::task::spawn(async move {
+/// This is the part of `run_oscquery_service` without the Zeroconf registration.
+pub(crate) fn serve_oscquery_http(
+    root: impl Into<OscTreeHandle>,
+    listener: TcpListener,
+) -> tokio::task::JoinHandle<()> {
+    let root = root.into();
+    tokio::task::spawn(async move {
+        let address = listener.local_addr().unwrap();
         loop {
             println!("oscq_rs wait for connection {:?}", address);
             let (stream, con) = listener.accept().await.unwrap();
             println!("oscq_rs serve connection {:?}", con);
             let service = OscQueryStatic {
-                root: arc_root.clone(),
+                root: root.clone(),
+                peer: con,
             };
             let io = TokioIo::new(stream);
             tokio::task::spawn(async move {
@@ -141,13 +217,41 @@ pub async fn run_oscquery_service(
                 if let Err(err) = http1::Builder::new()
                     .keep_alive(true)
                     .serve_connection(io, service)
+                    .with_upgrades()
                     .await
                 {
                     println!("Failed to serve connection: {:?}", err);
                 }
             });
         }
-    });
+    })
+}
+
+/// Runs an OSCQuery server on the given socket address, serving the OSCNode
+/// rooted at `root`.
+///
+/// `root` can either be a plain `OSCNode` or an `OscTreeHandle`. Keep a clone
+/// of the handle to update the tree while it is being served.
+///
+/// # Arguments
+///
+/// * `root` - The root node of the OSCNode tree to serve, or an `OscTreeHandle` to it.
+/// * `address` - The socket address on which to listen for incoming requests.
+///
+/// # Returns
+///
+/// Returns a tuple containing two `JoinHandle`s: one for the main service loop,
+/// and one for the Zeroconf service registration loop.
+pub async fn run_oscquery_service(
+    root: impl Into<OscTreeHandle>,
+    address: SocketAddr,
+) -> tokio::io::Result<(tokio::task::JoinHandle<()>, tokio::task::JoinHandle<()>)> {
+    println!("oscq_rs start tcp at {:?}", address);
+    let listener = TcpListener::bind(address).await?;
+    // an ephemeral port (0) is registered with the port it was bound to
+    let address = listener.local_addr()?;
+    println!("oscq_rs started tcp at {:?}", address);
+    let handle = serve_oscquery_http(root, listener);
 
     let handle1 = tokio::task::spawn(async move {
         let mut service = zeroconf::MdnsService::new(
@@ -173,13 +277,14 @@ pub async fn run_oscquery_service(
 ///
 /// # Arguments
 ///
-/// * `root` - The root node of the OSCNode tree to serve.
+/// * `root` - The root node of the OSCNode tree to serve, or an `OscTreeHandle` to it.
 /// * `address` - The socket address on which to listen for incoming requests.
 ///
 /// # Returns
 /// The function returns immediately after spawning the thread, and the thread will continue running until the process
 /// terminates or the thread panics.
-pub fn spawn_oscquery_service(root: OSCNode, address: SocketAddr) {
+pub fn spawn_oscquery_service(root: impl Into<OscTreeHandle>, address: SocketAddr) {
+    let root = root.into();
     spawn(move || {
         let rt = Runtime::new().unwrap();
         rt.block_on(async move {
@@ -188,23 +293,51 @@ pub fn spawn_oscquery_service(root: OSCNode, address: SocketAddr) {
             res.0.unwrap();
             res.1.unwrap();
         });
-        loop {
-            panic!("oscQueryServer Stopped");
-        }
+        panic!("oscQueryServer Stopped");
     });
 }
 
-/// This is Rust test that creates an OSCQuery server with three parameters,
-/// sets the IP address and port number, and runs the OSCQuery service on the specified address.
-/// It then creates a UDP socket to receive incoming OSC messages, binds it to a different address and port number,
-/// and enters a loop to receive and send back any incoming messages. Finally,
-/// it joins the futures for the OSCQuery server and the zeroconf server and unwraps their results.
+/// Serve `root` on an ephemeral port of localhost, returns the task and the bound address.
+#[cfg(test)]
+pub(crate) async fn serve_local(
+    root: impl Into<OscTreeHandle>,
+) -> (tokio::task::JoinHandle<()>, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    (serve_oscquery_http(root, listener), address)
+}
+
+/// Send a plain HTTP/1.1 GET request and return the status code and body of the response.
+#[cfg(test)]
+pub(crate) async fn http_get(address: SocketAddr, path_and_query: &str) -> (u16, String) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path_and_query, address
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default();
+    (status, body)
+}
+
+/// This is Rust test that creates an OSCQuery server with three parameters
+/// and runs the OSCQuery HTTP service on a free port of localhost.
+/// It then queries the served tree, adds another parameter through the shared `OscTreeHandle`
+/// and checks that the running service answers with the updated tree.
 #[tokio::test]
 async fn test_service() {
     // Import necessary modules
-    use crate::{OSCAccess, OSCUnit, OscHostInfo, OscQueryParameter};
+    use crate::{OSCAccess, OSCNode, OSCUnit, OscHostInfo, OscQueryParameter};
     use rosc::OscType;
-    use std::net::SocketAddr;
 
     // Create an instance of OscHostInfo with name "OSCQuery Test", IP address "127.0.0.1" and port number 6668
     let info = OscHostInfo::new("OSCQuery Test".to_string(), "127.0.0.1".to_string(), 6668)
@@ -220,7 +353,8 @@ async fn test_service() {
         .with_description("My First Description".to_string())
         .with_min_max(0f32, 10f32)
         .with_access(OSCAccess::ReadWrite)
-        .with_unit(OSCUnit::Distance(crate::OSCDistance::Centimeter));
+        .with_unit(OSCUnit::Distance(crate::OSCDistance::Centimeter))
+        .with_tag("lighting");
 
     let par2 = OscQueryParameter::new("/group/test2".to_string(), OscType::Float(1f32))
         .with_description("My First Description".to_string())
@@ -239,27 +373,510 @@ async fn test_service() {
     root.add(par2).unwrap();
     root.add(par3).unwrap();
 
-    // Set the IP address and port number for the oscquery service
-    let addr: SocketAddr = ([127, 0, 0, 1], 3000).into();
+    // Share the tree between the test and the service
+    let tree = OscTreeHandle::new(root);
 
-    // Run the oscquery service and get the futures for the server and the zeroconf server
-    let (x, y) = run_oscquery_service(root, addr).await.unwrap();
+    // Run the oscquery http service (without the zeroconf registration) on a free port
+    let (_server, addr) = serve_local(tree.clone()).await;
 
-    // Create a UDP socket for receiving incoming osc messages and bind it to the address and port number
-    let addr_osc: SocketAddr = ([127, 0, 0, 1], 6669).into();
-    let sock = tokio::net::UdpSocket::bind(addr_osc).await.unwrap();
-    let mut buf = [0; 1024];
+    let (status, body) = http_get(addr, "/group/test").await;
+    assert_eq!(status, 200);
+    assert!(body.contains("\"FULL_PATH\":\"/group/test\""));
+    // only the attributes of the enabled extensions are served
+    assert!(body.contains("\"UNIT\":[\"distance.cm\"]"));
+    assert!(!body.contains("lighting"));
+    // VALUE is disabled
+    assert!(!body.contains("\"VALUE\""));
+    assert_eq!(http_get(addr, "/group/test?VALUE").await.0, 400);
 
-    // Loop to receive incoming osc messages
-    loop {
-        let (len, addr) = sock.recv_from(&mut buf).await.unwrap();
-        println!("{:?} bytes received from {:?}", len, addr);
+    let (status, _) = http_get(addr, "/group/late").await;
+    assert_eq!(status, 404);
+
+    // Add a parameter while the service is running
+    tree.add(OscQueryParameter::new(
+        "/group/late".to_string(),
+        OscType::Int(7),
+    ))
+    .unwrap();
+
+    let (status, body) = http_get(addr, "/group/late").await;
+    assert_eq!(status, 200);
+    assert!(body.contains("\"FULL_PATH\":\"/group/late\""));
+}
+
+/// Checks that value changes are pushed to a WebSocket client after it sent a LISTEN command,
+/// and that no more values are pushed after IGNORE.
+#[tokio::test]
+async fn test_websocket_listen() {
+    use crate::{OSCNode, OscQueryParameter};
+    use futures_util::{SinkExt, StreamExt};
+    use rosc::{OscPacket, OscType};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(OscQueryParameter::new(
+        "/mixer/gain".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+
+    let (_server, addr) = serve_local(tree.clone()).await;
+
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    ws.send(Message::text(
+        r#"{"COMMAND":"LISTEN","DATA":"/mixer/gain"}"#,
+    ))
+    .await
+    .unwrap();
 
-        let len = sock.send_to(&buf[..len], addr).await.unwrap();
-        println!("{:?} bytes sent", len);
+    // the LISTEN command is handled asynchronously, retry until the value arrives
+    let received = loop {
+        tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.5)])
+            .unwrap();
+        let next = tokio::time::timeout(std::time::Duration::from_millis(100), ws.next()).await;
+        if let Ok(Some(Ok(Message::Binary(buf)))) = next {
+            break rosc::decoder::decode_udp(&buf).unwrap().1;
+        }
+    };
+    match received {
+        OscPacket::Message(msg) => {
+            assert_eq!(msg.addr, "/mixer/gain");
+            assert_eq!(msg.args, vec![OscType::Float(0.5)]);
+        }
+        OscPacket::Bundle(_) => panic!("expected a message"),
+    }
+
+    ws.send(Message::text(
+        r#"{"COMMAND":"IGNORE","DATA":"/mixer/gain"}"#,
+    ))
+    .await
+    .unwrap();
+    // drain values that were sent before IGNORE was handled
+    while let Ok(Some(_)) =
+        tokio::time::timeout(std::time::Duration::from_millis(100), ws.next()).await
+    {}
+
+    tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.7)])
+        .unwrap();
+    let next = tokio::time::timeout(std::time::Duration::from_millis(200), ws.next()).await;
+    assert!(next.is_err());
+
+    // namespace changes are pushed to every client
+    tree.add(OscQueryParameter::new(
+        "/mixer/mute".to_string(),
+        OscType::Bool(false),
+    ))
+    .unwrap();
+    match ws.next().await {
+        Some(Ok(Message::Text(text))) => assert_eq!(
+            text.as_str(),
+            r#"{"COMMAND":"PATH_ADDED","DATA":"/mixer/mute"}"#
+        ),
+        other => panic!("expected PATH_ADDED, got {:?}", other),
+    }
+}
+
+/// Checks that LISTEN and IGNORE accept any spelling of a path,
+/// and that LISTEN is ignored for unknown paths and disabled extensions
+#[tokio::test]
+async fn test_websocket_listen_paths() {
+    use crate::{OSCNode, OscHostInfo, OscQueryParameter};
+    use futures_util::{SinkExt, StreamExt};
+    use rosc::{OscPacket, OscType};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(OscQueryParameter::new(
+        "/mixer/gain".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    let (_server, addr) = serve_local(tree.clone()).await;
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    for listen in ["/mixer/late", "/mixer/gain/"] {
+        let command = format!(r#"{{"COMMAND":"LISTEN","DATA":"{}"}}"#, listen);
+        ws.send(Message::text(command)).await.unwrap();
+    }
+
+    // the commands are handled in order, once the value arrives "/mixer/late" was ignored
+    let received = loop {
+        tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.5)])
+            .unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(100), ws.next()).await;
+        if let Ok(Some(Ok(Message::Binary(buf)))) = next {
+            break rosc::decoder::decode_udp(&buf).unwrap().1;
+        }
+    };
+    match received {
+        OscPacket::Message(msg) => assert_eq!(msg.addr, "/mixer/gain"),
+        OscPacket::Bundle(_) => panic!("expected a message"),
+    }
+    tree.add(OscQueryParameter::new(
+        "/mixer/late".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    tree.set_value("/mixer/late".to_string(), vec![OscType::Float(0.5)])
+        .unwrap();
+    ws.send(Message::text(
+        r#"{"COMMAND":"IGNORE","DATA":"//mixer/gain"}"#,
+    ))
+    .await
+    .unwrap();
+    // drain the values and PATH_ADDED that were sent before IGNORE was handled
+    while let Ok(Some(Ok(msg))) = tokio::time::timeout(Duration::from_millis(100), ws.next()).await
+    {
+        if let Message::Binary(buf) = msg {
+            match rosc::decoder::decode_udp(&buf).unwrap().1 {
+                OscPacket::Message(msg) => assert_eq!(msg.addr, "/mixer/gain"),
+                OscPacket::Bundle(_) => panic!("expected a message"),
+            }
+        }
+    }
+    tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.7)])
+        .unwrap();
+    let next = tokio::time::timeout(Duration::from_millis(200), ws.next()).await;
+    assert!(next.is_err());
+
+    // the LISTEN extension is disabled
+    let info = OscHostInfo::from_name("OSCQuery Test".to_string()).with_ext_value();
+    let tree = OscTreeHandle::new(OSCNode::root(Some(Box::new(info))));
+    tree.add(OscQueryParameter::new(
+        "/mixer/gain".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    let (_server, addr) = serve_local(tree.clone()).await;
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    ws.send(Message::text(
+        r#"{"COMMAND":"LISTEN","DATA":"/mixer/gain"}"#,
+    ))
+    .await
+    .unwrap();
+    for _ in 0..3 {
+        tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.5)])
+            .unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(100), ws.next()).await;
+        assert!(next.is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_critical_streaming() {
+    use crate::{OSCNode, OscQueryParameter};
+    use futures_util::{SinkExt, StreamExt};
+    use rosc::{OscPacket, OscType};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(OscQueryParameter::new(
+        "/mixer/gain".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+    tree.add(OscQueryParameter::new("/scene/go".to_string(), OscType::Int(0)).with_critical(true))
+        .unwrap();
+
+    let (_server, addr) = serve_local(tree.clone()).await;
+
+    let udp = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    let start = format!(
+        r#"{{"COMMAND":"START_OSC_STREAMING","DATA":{{"LOCAL_SERVER_PORT":{}}}}}"#,
+        udp.local_addr().unwrap().port()
+    );
+    ws.send(Message::text(start)).await.unwrap();
+    ws.send(Message::text(
+        r#"{"COMMAND":"LISTEN","DATA":"/mixer/gain"}"#,
+    ))
+    .await
+    .unwrap();
+
+    // non critical values are streamed over UDP
+    let mut buf = [0u8; rosc::decoder::MTU];
+    let received = loop {
+        tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.5)])
+            .unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(100), udp.recv(&mut buf)).await;
+        if let Ok(Ok(len)) = next {
+            break rosc::decoder::decode_udp(&buf[..len]).unwrap().1;
+        }
+    };
+    match received {
+        OscPacket::Message(msg) => assert_eq!(msg.addr, "/mixer/gain"),
+        OscPacket::Bundle(_) => panic!("expected a message"),
+    }
+
+    // critical values stay on the WebSocket
+    ws.send(Message::text(r#"{"COMMAND":"LISTEN","DATA":"/scene/go"}"#))
+        .await
+        .unwrap();
+    let received = loop {
+        tree.set_value("/scene/go".to_string(), vec![OscType::Int(1)])
+            .unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(100), ws.next()).await;
+        if let Ok(Some(Ok(Message::Binary(buf)))) = next {
+            break rosc::decoder::decode_udp(&buf).unwrap().1;
+        }
+    };
+    match received {
+        OscPacket::Message(msg) => assert_eq!(msg.addr, "/scene/go"),
+        OscPacket::Bundle(_) => panic!("expected a message"),
+    }
+}
+
+/// Checks the answers to attribute queries like `/path?RANGE`
+#[tokio::test]
+async fn test_attribute_queries() {
+    use crate::{OSCAccess, OSCNode, OscHostInfo, OscQueryParameter};
+    use rosc::OscType;
+
+    let info = OscHostInfo::new("OSCQuery Test".to_string(), "127.0.0.1".to_string(), 6668)
+        .with_ext_value()
+        .with_ext_access()
+        .with_ext_range()
+        .with_ext_tags();
+    let mut root = OSCNode::root(Some(Box::new(info)));
+    root.add(
+        OscQueryParameter::new("/mixer/gain".to_string(), OscType::Float(0.5))
+            .with_min_max(0.0, 1.0)
+            .with_access(OSCAccess::ReadWrite)
+            .with_description("Gain".to_string()),
+    )
+    .unwrap();
+    root.add(
+        OscQueryParameter::new("/mixer/cue".to_string(), OscType::Int(1))
+            .with_access(OSCAccess::Write),
+    )
+    .unwrap();
+
+    let tree = OscTreeHandle::new(root);
+    let (_server, addr) = serve_local(tree.clone()).await;
+
+    let query = |path: &'static str| http_get(addr, path);
+    assert_eq!(
+        query("/mixer/gain?TYPE").await,
+        (200, r#"{"TYPE":"f"}"#.to_string())
+    );
+    assert_eq!(
+        query("/mixer/gain?ACCESS").await,
+        (200, r#"{"ACCESS":3}"#.to_string())
+    );
+    assert_eq!(
+        query("/mixer/gain?RANGE").await,
+        (200, r#"{"RANGE":[{"MAX":1.0,"MIN":0.0}]}"#.to_string())
+    );
+    assert_eq!(
+        query("/mixer/gain?FULL_PATH").await,
+        (200, r#"{"FULL_PATH":"/mixer/gain"}"#.to_string())
+    );
+    assert_eq!(query("/mixer?CONTENTS").await.0, 200);
+    // the node does not have the attribute
+    assert_eq!(query("/mixer/gain?TAGS").await.0, 204);
+    assert_eq!(query("/mixer?VALUE").await.0, 204);
+    // the values of write only parameters are not disclosed
+    assert_eq!(query("/mixer/cue?VALUE").await.0, 204);
+    // VALUE queries answer with the current state of the parameter
+    tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.25)])
+        .unwrap();
+    assert_eq!(
+        query("/mixer/gain?VALUE").await,
+        (200, r#"{"VALUE":[0.25]}"#.to_string())
+    );
+    // the extension is disabled or the attribute is unknown
+    assert_eq!(query("/mixer/gain?DESCRIPTION").await.0, 400);
+    assert_eq!(query("/mixer/gain?FOO").await.0, 400);
+}
+
+/// Checks that HOST_INFO is answered from every path
+/// and advertises the address of the OSC receiver once it is bound
+#[tokio::test]
+async fn test_host_info() {
+    use crate::{run_osc_receiver, OSCNode, OscHostInfo, OscQueryParameter};
+    use rosc::{OscMessage, OscPacket, OscType};
+
+    let mut root = OSCNode::root(Some(Box::new(OscHostInfo::from_name(
+        "OSCQuery Test".to_string(),
+    ))));
+    root.add(OscQueryParameter::new(
+        "/mixer/gain".to_string(),
+        OscType::Float(0.5),
+    ))
+    .unwrap();
+    let tree = OscTreeHandle::new(root);
+
+    let (_server, addr) = serve_local(tree.clone()).await;
+
+    // without a receiver, OSC is sent to the OSCQuery server itself
+    let (status, body) = http_get(addr, "/mixer/gain?HOST_INFO").await;
+    assert_eq!(status, 200);
+    assert!(body.starts_with(r#"{"NAME":"OSCQuery Test","EXTENSIONS":"#));
+    assert_eq!(http_get(addr, "/?HOST_INFO").await, (status, body));
+
+    // the receiver is bound to an ephemeral port on all interfaces
+    let _receiver = run_osc_receiver(tree.clone()).await.unwrap();
+    let (_, body) = http_get(addr, "/?HOST_INFO").await;
+    let host_info: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(host_info.get("OSC_IP"), None);
+    assert_eq!(host_info["OSC_TRANSPORT"], "UDP");
+    let port = host_info["OSC_PORT"].as_u64().unwrap() as u16;
+    assert_ne!(port, addr.port());
+
+    // OSC sent to the advertised port reaches the tree
+    let mut events = tree.subscribe();
+    let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let packet = rosc::encoder::encode(&OscPacket::Message(OscMessage {
+        addr: "/mixer/gain".to_string(),
+        args: vec![OscType::Float(0.25)],
+    }))
+    .unwrap();
+    socket
+        .send_to(&packet, SocketAddr::from(([127, 0, 0, 1], port)))
+        .await
+        .unwrap();
+    tokio::time::timeout(std::time::Duration::from_secs(1), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        tree.value("/mixer/gain".to_string()).unwrap(),
+        vec![OscType::Float(0.25)]
+    );
+
+    // without HOST_INFO there is nothing to answer
+    let (_server, addr) = serve_local(OSCNode::root(None)).await;
+    assert_eq!(http_get(addr, "/?HOST_INFO").await.0, 204);
+}
+
+/// Checks that binary OSC packets sent over the WebSocket set writable values
+/// and are echoed to the other listening clients, but not to the sender.
+#[tokio::test]
+async fn test_websocket_osc() {
+    use crate::{OSCAccess, OSCNode, OscQueryParameter};
+    use futures_util::{SinkExt, StreamExt};
+    use rosc::{OscMessage, OscPacket, OscType};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let tree = OscTreeHandle::new(OSCNode::root(None));
+    tree.add(
+        OscQueryParameter::new("/mixer/gain".to_string(), OscType::Float(0.0))
+            .with_access(OSCAccess::ReadWrite),
+    )
+    .unwrap();
+    tree.add(
+        OscQueryParameter::new("/mixer/meter".to_string(), OscType::Float(0.0))
+            .with_access(OSCAccess::Read),
+    )
+    .unwrap();
+
+    let (_server, addr) = serve_local(tree.clone()).await;
+
+    let listen = r#"{"COMMAND":"LISTEN","DATA":"/mixer/gain"}"#;
+    let (mut sender, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    sender.send(Message::text(listen)).await.unwrap();
+    let (mut listener, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    listener.send(Message::text(listen)).await.unwrap();
+
+    let packet = |addr: &str, value| {
+        Message::binary(
+            rosc::encoder::encode(&OscPacket::Message(OscMessage {
+                addr: addr.to_string(),
+                args: vec![OscType::Float(value)],
+            }))
+            .unwrap(),
+        )
+    };
+
+    // read only values are not set, the LISTEN command is handled asynchronously,
+    // so retry until the value of the writable parameter is echoed
+    let received = loop {
+        sender.send(packet("/mixer/meter", 1.0)).await.unwrap();
+        sender.send(packet("/mixer/gain", 0.5)).await.unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(100), listener.next()).await;
+        if let Ok(Some(Ok(Message::Binary(buf)))) = next {
+            break rosc::decoder::decode_udp(&buf).unwrap().1;
+        }
+    };
+    match received {
+        OscPacket::Message(msg) => {
+            assert_eq!(msg.addr, "/mixer/gain");
+            assert_eq!(msg.args, vec![OscType::Float(0.5)]);
+        }
+        OscPacket::Bundle(_) => panic!("expected a message"),
     }
+    assert_eq!(
+        tree.value("/mixer/gain".to_string()).unwrap(),
+        vec![OscType::Float(0.5)]
+    );
+    assert_eq!(
+        tree.value("/mixer/meter".to_string()).unwrap(),
+        vec![OscType::Float(0.0)]
+    );
+
+    // the value is not echoed back to the sender
+    let next = tokio::time::timeout(Duration::from_millis(200), sender.next()).await;
+    assert!(next.is_err());
+}
 
-    let res = tokio::join!(x, y);
-    res.0.unwrap();
-    res.1.unwrap();
+/// Checks that namespace changes are only pushed if the PATH_CHANGED extension is enabled
+#[tokio::test]
+async fn test_path_changed_extension() {
+    use crate::{OSCNode, OscHostInfo, OscQueryParameter};
+    use futures_util::{SinkExt, StreamExt};
+    use rosc::OscType;
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let info = OscHostInfo::new("OSCQuery Test".to_string(), "127.0.0.1".to_string(), 6668)
+        .with_ext_listen()
+        .with_ext_value();
+    let tree = OscTreeHandle::new(OSCNode::root(Some(Box::new(info))));
+    tree.add(OscQueryParameter::new(
+        "/mixer/gain".to_string(),
+        OscType::Float(0.0),
+    ))
+    .unwrap();
+
+    let (_server, addr) = serve_local(tree.clone()).await;
+    let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr))
+        .await
+        .unwrap();
+    ws.send(Message::text(
+        r#"{"COMMAND":"LISTEN","DATA":"/mixer/gain"}"#,
+    ))
+    .await
+    .unwrap();
+
+    // the value arrives once LISTEN was handled, no PATH_ADDED is sent before it
+    let mut added = 0;
+    loop {
+        tree.add(OscQueryParameter::new(
+            format!("/mixer/ch{}", added),
+            OscType::Float(0.0),
+        ))
+        .unwrap();
+        added += 1;
+        tree.set_value("/mixer/gain".to_string(), vec![OscType::Float(0.5)])
+            .unwrap();
+        match tokio::time::timeout(Duration::from_millis(100), ws.next()).await {
+            Ok(Some(Ok(Message::Binary(_)))) => break,
+            Ok(other) => panic!("expected only values, got {:?}", other),
+            Err(_) => {}
+        }
+    }
 }
@@ -0,0 +1,233 @@
+use crate::handle::normalize_path;
+use crate::receiver::apply_frame;
+use crate::tokiort::TokioIo;
+use crate::{ChangeSource, OscTreeHandle, TreeEvent};
+
+use futures_util::{SinkExt, StreamExt};
+use hyper::upgrade::Upgraded;
+use rosc::{OscMessage, OscPacket};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::net::UdpSocket;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Commands a client can send as JSON text frames over the OSCQuery WebSocket
+/// e.g. `{"COMMAND":"LISTEN","DATA":"/path"}`
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "COMMAND", content = "DATA")]
+enum ClientCommand {
+    /// start streaming the values of the node at the path to the client
+    #[serde(rename = "LISTEN")]
+    Listen(String),
+    /// stop streaming the values of the node at the path to the client
+    #[serde(rename = "IGNORE")]
+    Ignore(String),
+    /// stream the values of non critical nodes to the UDP port of the client instead,
+    /// e.g. `{"COMMAND":"START_OSC_STREAMING","DATA":{"LOCAL_SERVER_PORT":9000}}`
+    #[serde(rename = "START_OSC_STREAMING")]
+    StartOscStreaming {
+        #[serde(rename = "LOCAL_SERVER_PORT")]
+        local_server_port: u16,
+    },
+}
+
+/// Namespace change notifications the server sends as JSON text frames to all clients
+/// e.g. `{"COMMAND":"PATH_ADDED","DATA":"/path"}`
+#[derive(Debug, Serialize)]
+#[serde(tag = "COMMAND", content = "DATA")]
+enum PathCommand {
+    #[serde(rename = "PATH_ADDED")]
+    Added(String),
+    #[serde(rename = "PATH_REMOVED")]
+    Removed(String),
+    #[serde(rename = "PATH_RENAMED")]
+    Renamed {
+        #[serde(rename = "OLD")]
+        old: String,
+        #[serde(rename = "NEW")]
+        new: String,
+    },
+    #[serde(rename = "PATH_CHANGED")]
+    Changed(String),
+}
+
+impl PathCommand {
+    /// the notification for a change of the namespace, `None` for value changes
+    fn from_event(event: TreeEvent) -> Option<Self> {
+        match event {
+            TreeEvent::PathAdded(path) => Some(PathCommand::Added(path)),
+            TreeEvent::PathRemoved(path) => Some(PathCommand::Removed(path)),
+            TreeEvent::PathRenamed { old, new } => Some(PathCommand::Renamed { old, new }),
+            TreeEvent::PathChanged(path) => Some(PathCommand::Changed(path)),
+            TreeEvent::ValueChanged { .. } => None,
+        }
+    }
+}
+
+/// Serve one client connected over the OSCQuery WebSocket.
+///
+/// The client selects the paths it wants to follow with LISTEN and IGNORE commands,
+/// `/path/` and `/path` are the same path. LISTEN is ignored for unknown paths
+/// and if the LISTEN or VALUE extension is disabled.
+/// Every value change of a listened, readable path is pushed to the client as a binary OSC packet.
+/// Binary OSC packets sent by the client are applied to the writable parameters of the tree
+/// and echoed to the other listening clients, but not back to the client.
+/// After START_OSC_STREAMING the values are sent to the UDP port of the client instead,
+/// only CRITICAL nodes keep using the reliable WebSocket.
+/// If the PATH_CHANGED extension is enabled, changes of the namespace are pushed to every client
+/// as PATH_ADDED, PATH_REMOVED, PATH_RENAMED and PATH_CHANGED commands.
+/// Returns when the client closes the connection.
+pub(crate) async fn serve_websocket(
+    ws: WebSocketStream<TokioIo<Upgraded>>,
+    tree: OscTreeHandle,
+    peer: SocketAddr,
+) {
+    let (mut sink, mut stream) = ws.split();
+    let mut events = tree.subscribe();
+    let mut listening: HashSet<String> = HashSet::new();
+    let mut streaming: Option<(UdpSocket, SocketAddr)> = None;
+
+    loop {
+        tokio::select! {
+            msg = stream.next() => match msg {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<ClientCommand>(text.as_str()) {
+                        Ok(ClientCommand::Listen(path)) => {
+                            let path = normalize_path(&path);
+                            let root = tree.read();
+                            if !root.extensions().listen() {
+                                println!("oscq_rs LISTEN is disabled, ignored {} from {:?}", path, peer);
+                            } else if root.get(path.clone()).is_err() {
+                                println!("oscq_rs can not LISTEN to unknown {} from {:?}", path, peer);
+                            } else {
+                                listening.insert(path);
+                            }
+                        }
+                        Ok(ClientCommand::Ignore(path)) => {
+                            listening.remove(&normalize_path(&path));
+                        }
+                        Ok(ClientCommand::StartOscStreaming { local_server_port }) => {
+                            let target = SocketAddr::new(peer.ip(), local_server_port);
+                            match bind_udp(&target).await {
+                                Ok(socket) => streaming = Some((socket, target)),
+                                Err(err) => println!("oscq_rs can not stream to {:?}: {}", target, err),
+                            }
+                        }
+                        Err(err) => println!("oscq_rs unknown websocket command from {:?}: {}", peer, err),
+                    }
+                }
+                Some(Ok(Message::Binary(buf))) => {
+                    apply_frame(&tree, &buf, ChangeSource::WebSocket(peer));
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    println!("oscq_rs websocket error from {:?}: {:?}", peer, err);
+                    break;
+                }
+            },
+            event = events.recv() => match event {
+                Ok(TreeEvent::ValueChanged { path, value, source }) => {
+                    if !listening.contains(&path) || source == ChangeSource::WebSocket(peer) {
+                        continue;
+                    }
+                    let (readable, critical) = tree
+                        .read()
+                        .get(path.clone())
+                        .map(|node| (node.is_readable(), node.is_critical()))
+                        .unwrap_or((false, false));
+                    // the values of write only parameters are not disclosed
+                    if !readable {
+                        continue;
+                    }
+                    let packet = OscPacket::Message(OscMessage { addr: path, args: value });
+                    let buf = match rosc::encoder::encode(&packet) {
+                        Ok(buf) => buf,
+                        Err(err) => {
+                            println!("oscq_rs can not encode {:?}: {}", packet, err);
+                            continue;
+                        }
+                    };
+                    match &streaming {
+                        Some((socket, target)) if !critical => {
+                            if let Err(err) = socket.send_to(&buf, target).await {
+                                println!("oscq_rs can not stream to {:?}: {}", target, err);
+                            }
+                        }
+                        _ => {
+                            if sink.send(Message::binary(buf)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                Ok(event) => {
+                    let Some(command) = PathCommand::from_event(event) else {
+                        continue;
+                    };
+                    if !tree.read().extensions().path_changed() {
+                        continue;
+                    }
+                    let text = serde_json::to_string(&command).unwrap();
+                    if sink.send(Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(n)) => {
+                    println!("oscq_rs websocket {:?} dropped {} events", peer, n);
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    println!("oscq_rs websocket closed {:?}", peer);
+}
+
+/// bind a UDP socket on an ephemeral port that can send to `target`
+async fn bind_udp(target: &SocketAddr) -> std::io::Result<UdpSocket> {
+    match target {
+        SocketAddr::V4(_) => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await,
+        SocketAddr::V6(_) => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0)).await,
+    }
+}
+
+#[test]
+fn parse_client_commands() {
+    assert_eq!(
+        serde_json::from_str::<ClientCommand>(r#"{"COMMAND":"LISTEN","DATA":"/foo/bar"}"#).unwrap(),
+        ClientCommand::Listen("/foo/bar".to_string())
+    );
+    assert_eq!(
+        serde_json::from_str::<ClientCommand>(r#"{"COMMAND":"IGNORE","DATA":"/foo/bar"}"#).unwrap(),
+        ClientCommand::Ignore("/foo/bar".to_string())
+    );
+    assert_eq!(
+        serde_json::from_str::<ClientCommand>(
+            r#"{"COMMAND":"START_OSC_STREAMING","DATA":{"LOCAL_SERVER_PORT":9000,"LOCAL_SENDER_PORT":9001}}"#
+        )
+        .unwrap(),
+        ClientCommand::StartOscStreaming {
+            local_server_port: 9000
+        }
+    );
+    assert!(serde_json::from_str::<ClientCommand>(r#"{"COMMAND":"FOO","DATA":"/"}"#).is_err());
+}
+
+#[test]
+fn serialize_path_commands() {
+    assert_eq!(
+        serde_json::to_string(&PathCommand::Added("/foo".to_string())).unwrap(),
+        r#"{"COMMAND":"PATH_ADDED","DATA":"/foo"}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&PathCommand::Renamed {
+            old: "/foo".to_string(),
+            new: "/bar".to_string()
+        })
+        .unwrap(),
+        r#"{"COMMAND":"PATH_RENAMED","DATA":{"OLD":"/foo","NEW":"/bar"}}"#
+    );
+}